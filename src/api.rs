@@ -1,14 +1,26 @@
 use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
 use log::debug;
+use reqwest::StatusCode;
 use color_eyre::eyre::Result;
+use crate::auth::refresh_active_session;
 
 const WBR_API_BASE: &str = "https://www.whatbeatsrock.com/api/";
 const VS_ENDPOINT: &str = "vs";
 const SCORES_ENDPOINT: &str = "scores";
 const LIKE_ENDPOINT: &str = "me/custom/like";
+const MY_CUSTOM_ENDPOINT: &str = "me/custom";
+const CUSTOM_LIST_ENDPOINT: &str = "custom";
+
+static API_BASE: OnceLock<String> = OnceLock::new();
+
+/// Overrides the API base URL. Must be called before any requests are made.
+pub(crate) fn set_api_base(base: String) {
+    let _ = API_BASE.set(base);
+}
 
 pub(crate) fn endpoint_url(endpoint: &str) -> String {
-    WBR_API_BASE.to_owned() + endpoint
+    API_BASE.get().map(String::as_str).unwrap_or(WBR_API_BASE).to_owned() + endpoint
 }
 
 #[derive(serde::Serialize, Debug, Clone)]
@@ -25,7 +37,7 @@ pub(crate) struct CustomGameRequest {
     pub(crate) prev: String,
 }
 
-#[derive(serde::Serialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub(crate) struct LeaderboardRequest {
     pub(crate) gid: String,
     pub(crate) initials: String,
@@ -33,7 +45,7 @@ pub(crate) struct LeaderboardRequest {
     pub(crate) text: String,
 }
 
-#[derive(serde::Serialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub(crate) struct AuthenticatedLeaderboardRequest {
     pub(crate) gid: String,
     pub(crate) score: u64,
@@ -53,7 +65,7 @@ struct GameResponse {
     data: GameResponseInner,
 }
 
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CustomGameAttributes {
     pub(crate) title: String,
@@ -64,30 +76,86 @@ pub(crate) struct CustomGameAttributes {
 }
 
 
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub(crate) struct Vote {
     pub(crate) is_upvote: bool,
 }
 
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub(crate) struct CustomGame {
     pub(crate) id: String,
+    /// ID of the user who created the game, used as the oid when playing it
+    pub(crate) user_id: Option<String>,
+    pub(crate) created_at: Option<String>,
     pub(crate) attribute_data: CustomGameAttributes,
     pub(crate) execution_count: u64,
     pub(crate) denormalized_vote_count: u64,
+    #[serde(default)]
     pub(crate) vote: Vec<Vote>,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum VoteState {
+    None,
+    Liked,
+    Downvoted,
+}
+
+impl Display for VoteState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "not voted on"),
+            Self::Liked => write!(f, "liked"),
+            Self::Downvoted => write!(f, "downvoted"),
+        }
+    }
+}
+
 impl CustomGame {
-    /// Returns whether the user has liked this game
-    pub(crate) fn has_liked(&self) -> bool {
-        self.vote.len() == 1 && self.vote[0].is_upvote
+    /// Returns how the user has voted on this game
+    pub(crate) fn vote_state(&self) -> VoteState {
+        match self.vote.as_slice() {
+            [vote] if vote.is_upvote => VoteState::Liked,
+            [_] => VoteState::Downvoted,
+            _ => VoteState::None,
+        }
     }
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
 struct CustomResponse {
-    data: CustomGame,
+    /// `null` if the user has no custom game
+    #[serde(default)]
+    data: Option<CustomGame>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct CustomListResponse {
+    data: Vec<CustomGame>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct Profile {
+    pub(crate) id: String,
+    pub(crate) handle: String,
+    pub(crate) created_at: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct ProfileResponse {
+    pub(crate) data: Profile,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub(crate) struct Score {
+    pub(crate) score: u64,
+    pub(crate) text: String,
+    pub(crate) created_at: Option<String>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct ScoresResponse {
+    data: Vec<Score>,
 }
 
 #[derive(serde::Serialize, Debug, Clone)]
@@ -96,6 +164,11 @@ struct LikeRequest {
     is_upvote: bool,
 }
 
+#[derive(serde::Serialize, Debug, Clone)]
+struct RemoveLikeRequest {
+    fid: String,
+}
+
 #[derive(serde::Deserialize, Debug, Clone)]
 struct SuccessResponse {
     success: bool,
@@ -114,33 +187,53 @@ impl Display for ErrorResponse {
 
 impl std::error::Error for ErrorResponse {}
 
+impl ErrorResponse {
+    /// Whether the error is about the leaderboard initials, so different initials might be accepted
+    pub(crate) fn is_about_initials(&self) -> bool {
+        self.error.to_lowercase().contains("initials")
+    }
+}
+
+/// Sends a request, refreshing the access token and retrying once if the server says it has expired
+fn send_request(client: &reqwest::blocking::Client, build: impl Fn() -> reqwest::blocking::RequestBuilder) -> Result<String> {
+    let mut response = build().send()?;
+    if response.status() == StatusCode::UNAUTHORIZED && refresh_active_session(client)? {
+        debug!("retrying request after refreshing access token");
+        response = build().send()?;
+    }
+    Ok(response.text()?)
+}
+
 pub(crate) fn api_post(client: &reqwest::blocking::Client, endpoint: &str, payload: &str) -> Result<String> {
     debug!("request POST /api/{endpoint} {payload}");
-    let text = client.post(endpoint_url(endpoint))
+    let text = send_request(client, || client.post(endpoint_url(endpoint))
         .header("Content-Type", "application/json")
-        .body(payload.to_string())
-        .send()?
-        .text()?;
+        .body(payload.to_string()))?;
     debug!("response {text}");
     Ok(text)
 }
 
 pub(crate) fn api_put(client: &reqwest::blocking::Client, endpoint: &str, payload: &str) -> Result<String> {
     debug!("request PUT /api/{endpoint} {payload}");
-    let text = client.put(endpoint_url(endpoint))
+    let text = send_request(client, || client.put(endpoint_url(endpoint))
+        .header("Content-Type", "application/json")
+        .body(payload.to_string()))?;
+    debug!("response {text}");
+    Ok(text)
+}
+
+pub(crate) fn api_delete(client: &reqwest::blocking::Client, endpoint: &str, payload: &str) -> Result<String> {
+    debug!("request DELETE /api/{endpoint} {payload}");
+    let text = send_request(client, || client.delete(endpoint_url(endpoint))
         .header("Content-Type", "application/json")
-        .body(payload.to_string())
-        .send()?
-        .text()?;
+        .body(payload.to_string()))?;
     debug!("response {text}");
     Ok(text)
 }
 
 pub(crate) fn api_get(client: &reqwest::blocking::Client, endpoint: &str) -> Result<String> {
     debug!("request GET /api/{endpoint}");
-    let text = client.get(endpoint_url(endpoint))
-        .send()?
-        .text()?;
+    let text = send_request(client, || client.get(endpoint_url(endpoint)))?;
     debug!("response {text}");
     Ok(text)
 }
@@ -163,33 +256,98 @@ pub(crate) fn do_custom_guess(client: &reqwest::blocking::Client, guess: CustomG
     do_guess_internal(client, &json)
 }
 
+/// Parses a response that is either a `SuccessResponse` or an `ErrorResponse`
+fn parse_success(response: &str) -> Result<bool> {
+    match serde_json::from_str::<SuccessResponse>(response) {
+        Ok(success) => Ok(success.success),
+        Err(_) => Err(serde_json::from_str::<ErrorResponse>(response)?)?
+    }
+}
+
+fn submit_score_internal(client: &reqwest::blocking::Client, json: &str) -> Result<bool> {
+    let response = api_post(client, SCORES_ENDPOINT, json)?;
+    parse_success(&response)
+}
+
 pub(crate) fn submit_score(client: &reqwest::blocking::Client, request: LeaderboardRequest) -> Result<bool> {
     let json = serde_json::to_string(&request)?;
-    let response = api_post(client, SCORES_ENDPOINT, &json)?;
-    let success = serde_json::from_str::<SuccessResponse>(&response)?;
-    Ok(success.success)
+    submit_score_internal(client, &json)
 }
 
 pub(crate) fn submit_score_authenticated(client: &reqwest::blocking::Client, request: AuthenticatedLeaderboardRequest) -> Result<bool> {
     let json = serde_json::to_string(&request)?;
-    let response = api_post(client, SCORES_ENDPOINT, &json)?;
-    let success = serde_json::from_str::<SuccessResponse>(&response)?;
-    Ok(success.success)
+    submit_score_internal(client, &json)
 }
 
-pub(crate) fn get_custom_game(client: &reqwest::blocking::Client, oid: &str) -> Result<CustomGame> {
+/// Returns a user's custom game, or `None` if they don't have one
+pub(crate) fn get_custom_game(client: &reqwest::blocking::Client, oid: &str) -> Result<Option<CustomGame>> {
     let response = api_get(client, &format!("users/{oid}/custom"))?;
-    let game = serde_json::from_str::<CustomResponse>(&response)?;
-    Ok(game.data)
+    match serde_json::from_str::<CustomResponse>(&response) {
+        Ok(game) => Ok(game.data),
+        Err(_) => Err(serde_json::from_str::<ErrorResponse>(&response)?)?
+    }
 }
 
-pub(crate) fn like_custom_game(client: &reqwest::blocking::Client, fid: &str) -> Result<bool> {
+/// Returns a page of published custom games
+pub(crate) fn list_custom_games(client: &reqwest::blocking::Client, page: u64) -> Result<Vec<CustomGame>> {
+    let response = api_get(client, &format!("{CUSTOM_LIST_ENDPOINT}?page={page}"))?;
+    let games = serde_json::from_str::<CustomListResponse>(&response)?;
+    Ok(games.data)
+}
+
+/// Likes (`is_upvote` true) or downvotes a custom game
+pub(crate) fn vote_custom_game(client: &reqwest::blocking::Client, fid: &str, is_upvote: bool) -> Result<bool> {
     let request = LikeRequest {
         fid: fid.to_string(),
-        is_upvote: true,
+        is_upvote,
     };
     let json = serde_json::to_string(&request)?;
     let response = api_put(client, LIKE_ENDPOINT, &json)?;
     let success = serde_json::from_str::<SuccessResponse>(&response)?;
     Ok(success.success)
-}
\ No newline at end of file
+}
+
+/// Removes the user's like or downvote from a custom game
+pub(crate) fn remove_custom_game_vote(client: &reqwest::blocking::Client, fid: &str) -> Result<bool> {
+    let request = RemoveLikeRequest {
+        fid: fid.to_string(),
+    };
+    let json = serde_json::to_string(&request)?;
+    let response = api_delete(client, LIKE_ENDPOINT, &json)?;
+    let success = serde_json::from_str::<SuccessResponse>(&response)?;
+    Ok(success.success)
+}
+
+pub(crate) fn get_profile(client: &reqwest::blocking::Client, uid: &str) -> Result<Profile> {
+    let response = api_get(client, &format!("users/{uid}/profile"))?;
+    let profile = serde_json::from_str::<ProfileResponse>(&response)?;
+    Ok(profile.data)
+}
+
+pub(crate) fn get_profile_by_handle(client: &reqwest::blocking::Client, handle: &str) -> Result<Profile> {
+    let response = api_get(client, &format!("users?handle={}", urlencoding::encode(handle)))?;
+    let profile = serde_json::from_str::<ProfileResponse>(&response)?;
+    Ok(profile.data)
+}
+
+/// Returns a user's leaderboard scores, highest first
+pub(crate) fn get_user_scores(client: &reqwest::blocking::Client, uid: &str) -> Result<Vec<Score>> {
+    let response = api_get(client, &format!("users/{uid}/scores"))?;
+    let mut scores = serde_json::from_str::<ScoresResponse>(&response)?.data;
+    scores.sort_by_key(|s| std::cmp::Reverse(s.score));
+    Ok(scores)
+}
+
+/// Creates the current user's custom game
+pub(crate) fn create_custom_game(client: &reqwest::blocking::Client, attributes: &CustomGameAttributes) -> Result<bool> {
+    let json = serde_json::to_string(attributes)?;
+    let response = api_post(client, MY_CUSTOM_ENDPOINT, &json)?;
+    parse_success(&response)
+}
+
+/// Updates the current user's custom game
+pub(crate) fn update_custom_game(client: &reqwest::blocking::Client, attributes: &CustomGameAttributes) -> Result<bool> {
+    let json = serde_json::to_string(attributes)?;
+    let response = api_put(client, MY_CUSTOM_ENDPOINT, &json)?;
+    parse_success(&response)
+}
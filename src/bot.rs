@@ -0,0 +1,289 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Stdio};
+use std::time::Duration;
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use color_eyre::eyre::{bail, eyre, Result};
+use log::debug;
+use crate::cache::now;
+use crate::config::Config;
+use crate::custom::{load_custom_game, resolve_custom_oid};
+use crate::guess::{in_chain, normalise_guess};
+use crate::judge::create_judge;
+use crate::matchups::Matchups;
+use crate::save::data_file;
+use crate::{print_loss, print_win};
+
+const TRANSCRIPT_DIR: &str = "wbr_transcripts";
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum BotStrategy {
+    /// Guess words from a wordlist file, in order
+    Wordlist,
+    /// Guess the best guess that has beaten the word before
+    History,
+    /// Ask an external command for each guess
+    Command,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct BotArgs {
+    /// How the bot chooses its guesses
+    #[arg(long, value_enum)]
+    strategy: BotStrategy,
+
+    /// Wordlist file with one guess per line, for `--strategy wordlist`
+    #[arg(long, required_if_eq("strategy", "wordlist"))]
+    wordlist: Option<PathBuf>,
+
+    /// Command to run, for `--strategy command`. It is sent the word to beat on a line of its stdin,
+    /// and should reply with its guess on a line of its stdout (or an empty line to give up).
+    #[arg(long, required_if_eq("strategy", "command"))]
+    command: Option<String>,
+
+    /// Play a custom game, given the creator's handle, their user ID, or a link to the game
+    #[arg(short, long)]
+    custom: Option<String>,
+
+    /// Milliseconds to wait between guesses
+    #[arg(long, default_value_t = 1000)]
+    delay: u64,
+
+    /// Stop after this many guesses
+    #[arg(long, default_value_t = 50)]
+    max_rounds: u64,
+
+    /// Number of games to play
+    #[arg(long, default_value_t = 1)]
+    games: u64,
+
+    /// Directory to write transcripts to, defaults to `wbr_transcripts` in the data local directory
+    #[arg(long)]
+    transcript_dir: Option<PathBuf>,
+}
+
+/// Chooses the bot's guesses
+trait Strategy {
+    /// Returns the next guess, or `None` to give up. `chain` is every word used so far in the game.
+    fn next_guess(&mut self, prev_guess: &str, chain: &[String]) -> Result<Option<String>>;
+}
+
+struct WordlistStrategy {
+    words: Vec<String>,
+}
+
+impl Strategy for WordlistStrategy {
+    fn next_guess(&mut self, _prev_guess: &str, chain: &[String]) -> Result<Option<String>> {
+        Ok(self.words.iter().find(|word| !in_chain(chain, word)).cloned())
+    }
+}
+
+struct HistoryStrategy {
+    oid: Option<String>,
+    matchups: Matchups,
+}
+
+impl Strategy for HistoryStrategy {
+    fn next_guess(&mut self, prev_guess: &str, chain: &[String]) -> Result<Option<String>> {
+        Ok(self.matchups
+            .suggestions(self.oid.as_deref(), prev_guess)
+            .into_iter()
+            .find(|m| m.wins && !in_chain(chain, &m.guess))
+            .map(|m| m.guess.clone()))
+    }
+}
+
+struct CommandStrategy {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl CommandStrategy {
+    fn spawn(command: &str) -> Result<Self> {
+        let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+        let mut child = std::process::Command::new(shell)
+            .args([flag, command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or(eyre!("Could not open stdin of bot command"))?;
+        let stdout = BufReader::new(child.stdout.take().ok_or(eyre!("Could not open stdout of bot command"))?);
+        Ok(Self { child, stdin, stdout })
+    }
+}
+
+impl Strategy for CommandStrategy {
+    fn next_guess(&mut self, prev_guess: &str, _chain: &[String]) -> Result<Option<String>> {
+        writeln!(self.stdin, "{prev_guess}")?;
+        self.stdin.flush()?;
+        let mut guess = String::new();
+        self.stdout.read_line(&mut guess)?;
+        let guess = guess.trim();
+        Ok((!guess.is_empty()).then(|| guess.to_string()))
+    }
+}
+
+impl Drop for CommandStrategy {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+struct TranscriptRound {
+    prev: String,
+    guess: String,
+    wins: bool,
+    emoji: String,
+    reason: String,
+    cache_count: Option<u64>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+struct Transcript {
+    strategy: String,
+    is_custom: bool,
+    gid_oid: String,
+    started_at: u64,
+    score: u64,
+    rounds: Vec<TranscriptRound>,
+    /// Why the game ended early, if something went wrong
+    error: Option<String>,
+}
+
+impl Transcript {
+    /// Ends the game because of an error, keeping it in the transcript
+    fn fail(&mut self, error: String) {
+        eprintln!("{}", error.red());
+        self.error = Some(error);
+    }
+
+    fn save(&self, dir: &Path, game: u64) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}-{game}.json", self.started_at, self.gid_oid));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Plays games automatically, choosing guesses with a strategy
+pub(crate) fn run_bot(client: &reqwest::blocking::Client, config: &Config, args: BotArgs) -> Result<()> {
+    let oid = args.custom.as_deref().map(|custom| resolve_custom_oid(client, custom)).transpose()?;
+    let attributes = oid.as_deref().map(|oid| load_custom_game(client, oid, false)).transpose()?.map(|g| g.attribute_data);
+    let (start_guess, start_emoji, judging_criteria_win, judging_criteria_loss) = match &attributes {
+        Some(attributes) => (attributes.start_word.as_str(), attributes.start_emoji.as_str(), attributes.judging_criteria.as_str(), attributes.judging_criteria_loss.as_str()),
+        None => ("rock", "🪨", "beats", "does not beat"),
+    };
+
+    let mut strategy: Box<dyn Strategy> = match args.strategy {
+        BotStrategy::Wordlist => {
+            let path = args.wordlist.as_ref().ok_or(eyre!("--wordlist is required"))?;
+            let words = std::fs::read_to_string(path)?
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            if words.is_empty() {
+                bail!("Wordlist {} is empty", path.display());
+            }
+            Box::new(WordlistStrategy { words })
+        }
+        BotStrategy::History => Box::new(HistoryStrategy { oid: oid.clone(), matchups: Matchups::load()? }),
+        BotStrategy::Command => Box::new(CommandStrategy::spawn(args.command.as_deref().ok_or(eyre!("--command is required"))?)?),
+    };
+    let strategy_name = args.strategy.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default();
+    let transcript_dir = match args.transcript_dir {
+        Some(dir) => dir,
+        None => data_file(TRANSCRIPT_DIR)?,
+    };
+
+    for game in 1..=args.games {
+        let gid_oid = oid.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        debug!("bot game {game} gid/oid {gid_oid}");
+        let judge = create_judge(client, config, oid.is_some(), &gid_oid, judging_criteria_win)?;
+
+        let mut transcript = Transcript {
+            strategy: strategy_name.clone(),
+            is_custom: oid.is_some(),
+            gid_oid,
+            started_at: now(),
+            score: 0,
+            rounds: Vec::new(),
+            error: None,
+        };
+        let mut chain = vec![start_guess.to_string()];
+        let mut prev_emoji = start_emoji.to_string();
+
+        for round in 1..=args.max_rounds {
+            let prev_guess = chain.last().cloned().unwrap_or_default();
+            // errors end the game rather than the bot, so the transcript still gets saved
+            let guess = match strategy.next_guess(&prev_guess, &chain) {
+                Ok(Some(guess)) => guess,
+                Ok(None) => {
+                    println!("{}", "The bot has run out of guesses".italic().blue());
+                    break;
+                }
+                Err(e) => {
+                    transcript.fail(format!("The bot could not choose a guess: {e}"));
+                    break;
+                }
+            };
+
+            let guess = match normalise_guess(&guess) {
+                Ok(guess) if in_chain(&chain, &guess) => {
+                    transcript.fail(format!("The bot's guess {guess} has already been used in this game"));
+                    break;
+                }
+                Ok(guess) => guess,
+                Err(e) => {
+                    transcript.fail(format!("The bot's guess {guess:?} is invalid: {e}"));
+                    break;
+                }
+            };
+            let response = match judge.judge(&guess, &prev_guess) {
+                Ok(response) => response,
+                Err(e) => {
+                    transcript.fail(format!("API error: {e}"));
+                    break;
+                }
+            };
+            transcript.rounds.push(TranscriptRound {
+                prev: prev_guess.clone(),
+                guess: guess.clone(),
+                wins: response.guess_wins,
+                emoji: response.guess_emoji.clone(),
+                reason: response.reason.clone(),
+                cache_count: response.cache_count,
+            });
+
+            if !response.guess_wins {
+                print_loss(&guess, &response.guess_emoji, judging_criteria_loss, &prev_guess, &prev_emoji);
+                println!("{}", response.reason.red());
+                break;
+            }
+            print_win(&guess, &response.guess_emoji, judging_criteria_win, &prev_guess, &prev_emoji);
+            println!("{}", response.reason.green());
+            transcript.score += 1;
+            chain.push(guess);
+            prev_emoji = response.guess_emoji;
+
+            if round < args.max_rounds {
+                std::thread::sleep(Duration::from_millis(args.delay));
+            }
+        }
+
+        println!("{} {} {}", "The bot made".blue(), transcript.score.to_string().bold().blue(), "correct guesses".blue());
+        let path = transcript.save(&transcript_dir, game)?;
+        println!("{} {}", "Transcript saved to".italic().blue(), path.display().to_string().italic().blue());
+
+        if game < args.games {
+            std::thread::sleep(Duration::from_millis(args.delay));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use clap::ValueEnum;
+use color_eyre::eyre::{bail, eyre, Result};
+use rookie::enums::Cookie;
+
+#[derive(ValueEnum, serde::Serialize, serde::Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Browser {
+    Firefox,
+    Librewolf,
+    /// Linux only
+    Cachy,
+    Chrome,
+    Chromium,
+    Brave,
+    Arc,
+    Edge,
+    Vivaldi,
+    Opera,
+    OperaGx,
+    /// macOS only
+    Safari,
+}
+
+impl Display for Browser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Browser {
+    /// Returns the browsers that are searched when no browser is specified
+    pub(crate) fn all() -> Vec<Self> {
+        let mut browsers = vec![Self::Firefox, Self::Librewolf, Self::Opera, Self::Edge, Self::Chromium, Self::Brave, Self::Vivaldi, Self::Chrome];
+
+        #[cfg(target_os = "linux")]
+        browsers.push(Self::Cachy);
+
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        browsers.push(Self::OperaGx);
+
+        #[cfg(target_os = "macos")]
+        browsers.push(Self::Safari);
+
+        browsers
+    }
+
+    /// Loads cookies from the default profile of this browser
+    pub(crate) fn load_cookies(self, domains: Option<Vec<String>>) -> Result<Vec<Cookie>> {
+        Ok(match self {
+            Self::Firefox => rookie::firefox(domains)?,
+            Self::Librewolf => rookie::librewolf(domains)?,
+            #[cfg(target_os = "linux")]
+            Self::Cachy => rookie::cachy(domains)?,
+            #[cfg(not(target_os = "linux"))]
+            Self::Cachy => bail!("Cachy Browser is only supported on Linux"),
+            Self::Chrome => rookie::chrome(domains)?,
+            Self::Chromium => rookie::chromium(domains)?,
+            Self::Brave => rookie::brave(domains)?,
+            Self::Arc => rookie::arc(domains)?,
+            Self::Edge => rookie::edge(domains)?,
+            Self::Vivaldi => rookie::vivaldi(domains)?,
+            Self::Opera => rookie::opera(domains)?,
+            Self::OperaGx => rookie::opera_gx(domains)?,
+            #[cfg(target_os = "macos")]
+            Self::Safari => rookie::safari(domains)?,
+            #[cfg(not(target_os = "macos"))]
+            Self::Safari => bail!("Safari is only supported on macOS"),
+        })
+    }
+
+    /// Loads cookies from a cookie database belonging to this browser
+    fn load_db_cookies(self, db: &Path, domains: Option<Vec<String>>) -> Result<Vec<Cookie>> {
+        let db = db.to_path_buf();
+        Ok(match self {
+            Self::Firefox | Self::Librewolf | Self::Cachy => rookie::firefox_based(db, domains)?,
+            #[cfg(target_os = "macos")]
+            Self::Safari => rookie::safari_based(db, domains)?,
+            #[cfg(not(target_os = "macos"))]
+            Self::Safari => bail!("Safari is only supported on macOS"),
+            Self::Chrome | Self::Chromium | Self::Brave | Self::Arc | Self::Edge | Self::Vivaldi | Self::Opera | Self::OperaGx => {
+                load_chromium_cookies(self, db, domains)?
+            }
+        })
+    }
+}
+
+/// Chromium-based browsers on Windows encrypt cookies with a key kept in the `Local State` file of the browser's user data
+/// directory, which is the parent of the profile directory
+#[cfg(target_os = "windows")]
+fn load_chromium_cookies(browser: Browser, db: PathBuf, domains: Option<Vec<String>>) -> Result<Vec<Cookie>> {
+    let key = find_local_state(&db).ok_or(eyre!("Could not find the Local State file of the {browser} profile {}", db.display()))?;
+    rookie::chromium_based(key, db, domains)
+}
+
+#[cfg(unix)]
+fn load_chromium_cookies(browser: Browser, db: PathBuf, domains: Option<Vec<String>>) -> Result<Vec<Cookie>> {
+    use rookie::config;
+    let config = match browser {
+        Browser::Chrome => &config::CHROME_CONFIG,
+        Browser::Chromium => &config::CHROMIUM_CONFIG,
+        Browser::Brave => &config::BRAVE_CONFIG,
+        Browser::Arc => &config::ARC_CONFIG,
+        Browser::Edge => &config::EDGE_CONFIG,
+        Browser::Vivaldi => &config::VIVALDI_CONFIG,
+        Browser::Opera => &config::OPERA_CONFIG,
+        Browser::OperaGx => &config::OPERA_GX_CONFIG,
+        _ => bail!("{browser} is not based on Chromium"),
+    };
+    rookie::chromium_based(config, db, domains)
+}
+
+/// Finds the `Local State` file holding the cookie encryption key of a Chromium-based browser profile
+fn find_local_state(db: &Path) -> Option<PathBuf> {
+    db.ancestors()
+        .map(|dir| dir.join("Local State"))
+        .find(|path| path.is_file())
+}
+
+/// Finds the cookie database for a browser profile, which may be given as the profile directory or the database itself
+fn find_cookie_db(profile: &Path) -> Result<PathBuf> {
+    if profile.is_file() {
+        return Ok(profile.to_path_buf());
+    }
+
+    ["cookies.sqlite", "Network/Cookies", "Cookies"]
+        .iter()
+        .map(|name| profile.join(name))
+        .find(|path| path.is_file())
+        .ok_or(eyre!("Could not find a cookie database in {}", profile.display()))
+}
+
+/// Loads cookies from a specific browser profile. If the browser isn't given, it is guessed from the cookie database.
+pub(crate) fn load_profile_cookies(profile: &Path, browser: Option<Browser>, domains: Option<Vec<String>>) -> Result<Vec<Cookie>> {
+    let db = find_cookie_db(profile)?;
+    match browser {
+        Some(browser) => browser.load_db_cookies(&db, domains),
+        None => {
+            let key = find_local_state(&db);
+            rookie::any_browser(&db.to_string_lossy(), domains, key.as_ref().map(|key| key.to_string_lossy()).as_deref())
+        }
+    }
+}
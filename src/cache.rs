@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use color_eyre::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::save::{data_file, write_private};
+
+/// Returns the current time as seconds since the Unix epoch
+pub(crate) fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct Cached<T> {
+    pub(crate) cached_at: u64,
+    pub(crate) value: T,
+}
+
+impl<T> Cached<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            cached_at: now(),
+            value,
+        }
+    }
+
+    pub(crate) fn is_fresh(&self, ttl: Duration) -> bool {
+        now().saturating_sub(self.cached_at) < ttl.as_secs()
+    }
+}
+
+/// A file-backed cache of values with a time-to-live
+pub(crate) struct Cache<T> {
+    file: &'static str,
+    ttl: Duration,
+    entries: HashMap<String, Cached<T>>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> Cache<T> {
+    /// Loads a cache from the data local directory, discarding expired entries
+    pub(crate) fn load(file: &'static str, ttl: Duration) -> Result<Self> {
+        let path = data_file(file)?;
+        let mut entries = if path.exists() {
+            let json = std::fs::read_to_string(&path)?;
+            // a corrupt cache isn't worth failing over
+            serde_json::from_str::<HashMap<String, Cached<T>>>(&json).unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        entries.retain(|_, entry| entry.is_fresh(ttl));
+        Ok(Self { file, ttl, entries })
+    }
+
+    /// Saves the cache. It is only readable by the current user, as cached values may be sensitive.
+    pub(crate) fn save(&self) -> Result<()> {
+        write_private(&data_file(self.file)?, &serde_json::to_string(&self.entries)?)
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<T> {
+        self.entries.get(key).filter(|entry| entry.is_fresh(self.ttl)).map(|entry| entry.value.clone())
+    }
+
+    pub(crate) fn insert(&mut self, key: String, value: T) {
+        self.entries.insert(key, Cached::new(value));
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// Removes every entry whose key doesn't match `keep`
+    pub(crate) fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+}
@@ -0,0 +1,208 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use clap::ValueEnum;
+use colored::Colorize;
+use log::warn;
+use color_eyre::eyre::{bail, eyre, Result};
+use url::Url;
+use crate::browser::Browser;
+use crate::judge::JudgeBackend;
+use crate::leaderboard::{normalise_initials, LeaderboardBackend};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// All keys that can be used with `wbr config`
+pub(crate) const CONFIG_KEYS: &[&str] = &["initials", "account", "auto_submit", "color", "api_base", "leaderboard", "browser", "browser_profile", "judge", "rules_file", "llm_url", "llm_model"];
+
+#[derive(ValueEnum, serde::Serialize, serde::Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ColorMode {
+    /// Use colors if the terminal supports them
+    #[default]
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub(crate) struct Config {
+    /// Default initials for anonymous leaderboard submissions
+    pub(crate) initials: Option<String>,
+    /// Handle of the account to use when multiple are logged in
+    pub(crate) account: Option<String>,
+    /// Whether to submit scores to the leaderboard without asking
+    pub(crate) auto_submit: Option<bool>,
+    pub(crate) color: Option<ColorMode>,
+    /// Base URL of the What Beats Rock API
+    pub(crate) api_base: Option<String>,
+    pub(crate) leaderboard: Option<LeaderboardBackend>,
+    /// Browser to load cookies from
+    pub(crate) browser: Option<Browser>,
+    /// Browser profile directory or cookie database to load cookies from
+    pub(crate) browser_profile: Option<PathBuf>,
+    /// How guesses are judged
+    pub(crate) judge: Option<JudgeBackend>,
+    /// Rule file used by the `rules` judge
+    pub(crate) rules_file: Option<PathBuf>,
+    /// Base URL of the OpenAI-compatible API used by the `llm` judge
+    pub(crate) llm_url: Option<String>,
+    /// Model used by the `llm` judge
+    pub(crate) llm_model: Option<String>,
+}
+
+impl Config {
+    fn config_file() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().ok_or(std::io::Error::new(ErrorKind::NotFound, "Could not find config directory!"))?;
+        Ok(config_dir.join("wbr").join(CONFIG_FILE))
+    }
+
+    /// Loads the config file, or the defaults if it doesn't exist.
+    /// An invalid file is moved aside to `config.toml.invalid` rather than failing, so `wbr config` can still be used to fix it.
+    pub(crate) fn load() -> Result<Self> {
+        let path = Self::config_file()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let toml = std::fs::read_to_string(&path)?;
+        match toml::from_str::<Self>(&toml) {
+            Ok(config) => Ok(config),
+            Err(e) => {
+                let backup = path.with_file_name(format!("{CONFIG_FILE}.invalid"));
+                warn!("{} is invalid ({}), moving it to {} and using the default settings", path.display(), e.message(), backup.display());
+                std::fs::rename(&path, &backup)?;
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        let path = Self::config_file()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Applies the color mode to terminal output
+    pub(crate) fn apply_color(&self) {
+        match self.color.unwrap_or_default() {
+            ColorMode::Auto => {},
+            ColorMode::Always => colored::control::set_override(true),
+            ColorMode::Never => colored::control::set_override(false),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(match key {
+            "initials" => self.initials.clone(),
+            "account" => self.account.clone(),
+            "auto_submit" => self.auto_submit.map(|b| b.to_string()),
+            "color" => self.color.and_then(|c| c.to_possible_value()).map(|v| v.get_name().to_string()),
+            "api_base" => self.api_base.clone(),
+            "leaderboard" => self.leaderboard.and_then(|l| l.to_possible_value()).map(|v| v.get_name().to_string()),
+            "browser" => self.browser.map(|b| b.to_string()),
+            "browser_profile" => self.browser_profile.as_ref().map(|p| p.display().to_string()),
+            "judge" => self.judge.and_then(|j| j.to_possible_value()).map(|v| v.get_name().to_string()),
+            "rules_file" => self.rules_file.as_ref().map(|p| p.display().to_string()),
+            "llm_url" => self.llm_url.clone(),
+            "llm_model" => self.llm_model.clone(),
+            _ => bail!("Unknown config key {key} (valid keys: {})", CONFIG_KEYS.join(", ")),
+        })
+    }
+
+    pub(crate) fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "initials" => self.initials = Some(normalise_initials(value).map_err(|e| eyre!(e))?),
+            "account" => self.account = Some(value.strip_prefix('@').unwrap_or(value).to_string()),
+            "auto_submit" => self.auto_submit = Some(value.parse().map_err(|_| eyre!("auto_submit must be true or false"))?),
+            "color" => self.color = Some(ColorMode::from_str(value, true).map_err(|e| eyre!(e))?),
+            "api_base" => {
+                let mut url = Url::parse(value)?;
+                if !url.path().ends_with('/') {
+                    url.set_path(&format!("{}/", url.path()));
+                }
+                self.api_base = Some(url.to_string());
+            }
+            "leaderboard" => self.leaderboard = Some(LeaderboardBackend::from_str(value, true).map_err(|e| eyre!(e))?),
+            "browser" => self.browser = Some(Browser::from_str(value, true).map_err(|e| eyre!(e))?),
+            "browser_profile" => self.browser_profile = Some(PathBuf::from(value)),
+            "judge" => self.judge = Some(JudgeBackend::from_str(value, true).map_err(|e| eyre!(e))?),
+            "rules_file" => self.rules_file = Some(PathBuf::from(value)),
+            "llm_url" => self.llm_url = Some(Url::parse(value)?.to_string()),
+            "llm_model" => self.llm_model = Some(value.to_string()),
+            _ => bail!("Unknown config key {key} (valid keys: {})", CONFIG_KEYS.join(", ")),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn unset(&mut self, key: &str) -> Result<()> {
+        match key {
+            "initials" => self.initials = None,
+            "account" => self.account = None,
+            "auto_submit" => self.auto_submit = None,
+            "color" => self.color = None,
+            "api_base" => self.api_base = None,
+            "leaderboard" => self.leaderboard = None,
+            "browser" => self.browser = None,
+            "browser_profile" => self.browser_profile = None,
+            "judge" => self.judge = None,
+            "rules_file" => self.rules_file = None,
+            "llm_url" => self.llm_url = None,
+            "llm_model" => self.llm_model = None,
+            _ => bail!("Unknown config key {key} (valid keys: {})", CONFIG_KEYS.join(", ")),
+        }
+        Ok(())
+    }
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub(crate) enum ConfigCommand {
+    /// Print the value of a config key
+    Get {
+        key: String,
+    },
+    /// Set a config key
+    Set {
+        key: String,
+        value: String,
+    },
+    /// Reset a config key to its default
+    Unset {
+        key: String,
+    },
+    /// List all config keys and their values
+    List,
+}
+
+pub(crate) fn run_config_command(mut config: Config, command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Get { key } => {
+            match config.get(&key)? {
+                Some(value) => println!("{value}"),
+                None => println!("{}", "(not set)".italic()),
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            config.set(&key, &value)?;
+            config.save()?;
+        }
+        ConfigCommand::Unset { key } => {
+            config.unset(&key)?;
+            config.save()?;
+        }
+        ConfigCommand::List => {
+            for key in CONFIG_KEYS {
+                match config.get(key)? {
+                    Some(value) => println!("{} = {}", key.bold(), value),
+                    None => println!("{} = {}", key.bold(), "(not set)".italic()),
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,497 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+use clap::{Args, Subcommand, ValueEnum};
+use log::debug;
+use colored::Colorize;
+use color_eyre::eyre::{bail, eyre, Result};
+use url::Url;
+use uuid::Uuid;
+use crate::api::{create_custom_game, get_custom_game, get_profile, list_custom_games, remove_custom_game_vote, update_custom_game, vote_custom_game, CustomGame, CustomGameAttributes};
+use crate::auth::{active_user_id, get_user_id, AuthInfo};
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::history::History;
+use crate::{print_loss, print_prompt, print_win, read_yes_no_prompt, run_custom};
+
+const CUSTOM_GAME_CACHE_FILE: &str = "wbr_custom_cache.json";
+const CUSTOM_GAME_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// The most pages `wbr custom browse` will load, however many are asked for
+const MAX_BROWSE_PAGES: u64 = 50;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum CustomCommand {
+    /// Create your custom game
+    Create(CustomGameOptions),
+    /// Edit your custom game
+    Edit(CustomGameOptions),
+    /// Show your custom game
+    Show,
+    /// Like a user's custom game
+    Like {
+        /// Handle of the user whose custom game to like, their user ID, or a link to the game
+        user: String,
+    },
+    /// Remove your like or downvote from a user's custom game
+    Unlike {
+        /// Handle of the user whose custom game to remove your vote from, their user ID, or a link to the game
+        user: String,
+    },
+    /// Downvote a user's custom game
+    Downvote {
+        /// Handle of the user whose custom game to downvote, their user ID, or a link to the game
+        user: String,
+    },
+    /// Show information about a user's custom game without playing it
+    Info {
+        /// Handle of the user whose custom game to show, their user ID, or a link to the game
+        user: String,
+    },
+    /// Browse and search custom games, and pick one to play
+    Browse {
+        /// How to sort the games. Only the loaded pages are sorted, so load more with `--pages` to sort more games.
+        #[arg(long, value_enum, default_value_t)]
+        sort: BrowseSort,
+        /// Only show games whose title contains this text. Only the loaded pages are searched.
+        #[arg(long)]
+        search: Option<String>,
+        /// Maximum number of pages of games to load, at most 50
+        #[arg(long, default_value_t = 10)]
+        pages: u64,
+    },
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, Default)]
+pub(crate) enum BrowseSort {
+    /// Most liked first
+    #[default]
+    Likes,
+    /// Most played first
+    Plays,
+    /// Newest first
+    Recent,
+}
+
+/// Custom game settings. Any that aren't given are asked for interactively.
+#[derive(Args, Debug)]
+pub(crate) struct CustomGameOptions {
+    /// The title of the game
+    #[arg(long)]
+    title: Option<String>,
+    /// The word the game starts with, e.g. "rock"
+    #[arg(long)]
+    start_word: Option<String>,
+    /// The emoji for the start word, e.g. "🪨"
+    #[arg(long)]
+    start_emoji: Option<String>,
+    /// The phrase used when a guess wins, e.g. "beats"
+    #[arg(long)]
+    win: Option<String>,
+    /// The phrase used when a guess loses, e.g. "does not beat"
+    #[arg(long)]
+    loss: Option<String>,
+}
+
+/// Reads a custom game setting, using the current value if the input is empty
+fn read_setting(name: &str, current: Option<&str>) -> Result<String> {
+    loop {
+        match current {
+            Some(current) => print!("{} {}{} ", name.blue(), format!("[{current}]").italic(), ":".blue()),
+            None => print!("{} ", format!("{name}:").blue()),
+        }
+        std::io::stdout().flush()?;
+        let mut buf = String::new();
+        std::io::stdin().read_line(&mut buf)?;
+        let value = buf.trim();
+
+        match (value.is_empty(), current) {
+            (false, _) => break Ok(value.to_string()),
+            (true, Some(current)) => break Ok(current.to_string()),
+            (true, None) => println!("{}", format!("{name} can't be empty!").red()),
+        }
+    }
+}
+
+fn read_attributes(options: CustomGameOptions, current: Option<&CustomGameAttributes>) -> Result<CustomGameAttributes> {
+    let read = |value: Option<String>, name: &str, current: Option<&str>| match value {
+        Some(value) => Ok(value),
+        None => read_setting(name, current),
+    };
+
+    Ok(CustomGameAttributes {
+        title: read(options.title, "Title", current.map(|c| c.title.as_str()))?,
+        start_word: read(options.start_word, "Start word", current.map(|c| c.start_word.as_str()))?,
+        start_emoji: read(options.start_emoji, "Start emoji", current.map(|c| c.start_emoji.as_str()))?,
+        judging_criteria: read(options.win, "Win phrase (e.g. \"beats\")", current.map(|c| c.judging_criteria.as_str()))?,
+        judging_criteria_loss: read(options.loss, "Loss phrase (e.g. \"does not beat\")", current.map(|c| c.judging_criteria_loss.as_str()))?,
+    })
+}
+
+/// Shows how the game's prompts will look when playing
+fn print_preview(attributes: &CustomGameAttributes) {
+    println!("{}", "Preview:".italic());
+    print_prompt(&attributes.judging_criteria, &attributes.start_word, &attributes.start_emoji);
+    println!("{}", "paper".italic());
+    print_win("paper", "📄", &attributes.judging_criteria, &attributes.start_word, &attributes.start_emoji);
+    print_loss("paper", "📄", &attributes.judging_criteria_loss, &attributes.start_word, &attributes.start_emoji);
+}
+
+fn print_attributes(attributes: &CustomGameAttributes) {
+    println!("{} {}", "Title:".blue(), attributes.title.bold());
+    println!("{} {} {}", "Start:".blue(), attributes.start_word.bold(), attributes.start_emoji);
+    println!("{} {}", "Win phrase:".blue(), attributes.judging_criteria.bold());
+    println!("{} {}", "Loss phrase:".blue(), attributes.judging_criteria_loss.bold());
+}
+
+/// A custom game given by the user, before any handle has been looked up
+#[derive(Debug, PartialEq, Eq)]
+enum CustomGameRef {
+    /// The oid of the game, which is its creator's user ID
+    Oid(String),
+    /// The handle of the game's creator, without the @
+    Handle(String),
+}
+
+/// Parses a user's handle (with or without the @), their user ID, or a whatbeatsrock.com link to a custom game
+fn parse_custom_game_ref(input: &str) -> Result<CustomGameRef> {
+    let input = input.trim();
+    let is_wbr_url = |url: &Url| url.host_str().is_some_and(|host| host == "whatbeatsrock.com" || host.ends_with(".whatbeatsrock.com"));
+
+    // links may be pasted without the https://
+    let url = Url::parse(input)
+        .ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+        .or_else(|| Url::parse(&format!("https://{input}")).ok().filter(is_wbr_url));
+
+    if let Some(url) = url {
+        if !is_wbr_url(&url) {
+            bail!("{input} is not a whatbeatsrock.com link");
+        }
+        if let Some((_, oid)) = url.query_pairs().find(|(key, _)| key == "id" || key == "oid") {
+            return parse_custom_game_ref(&oid);
+        }
+        let segment = url.path_segments()
+            .and_then(|mut segments| segments.rfind(|s| !s.is_empty() && *s != "custom"))
+            .ok_or(eyre!("Could not find a custom game in the link {input}"))?;
+        return parse_custom_game_ref(&urlencoding::decode(segment)?);
+    }
+
+    if let Ok(oid) = Uuid::parse_str(input) {
+        return Ok(CustomGameRef::Oid(oid.to_string()));
+    }
+    Ok(CustomGameRef::Handle(input.strip_prefix('@').unwrap_or(input).to_string()))
+}
+
+/// Finds the oid of a custom game from a user's handle (with or without the @), their user ID, or a whatbeatsrock.com
+/// link to the game
+pub(crate) fn resolve_custom_oid(client: &reqwest::blocking::Client, input: &str) -> Result<String> {
+    match parse_custom_game_ref(input)? {
+        CustomGameRef::Oid(oid) => Ok(oid),
+        CustomGameRef::Handle(handle) => get_user_id(client, &handle),
+    }
+}
+
+/// Games are cached per account, as they include the account's vote on them
+fn cache_key(oid: &str) -> String {
+    format!("{}/{oid}", active_user_id().unwrap_or_default())
+}
+
+/// Fetches a user's custom game, or `None` if they don't have one.
+/// Unless `refresh` is set, a cached copy is returned if there is a fresh one.
+pub(crate) fn fetch_custom_game(client: &reqwest::blocking::Client, oid: &str, refresh: bool) -> Result<Option<CustomGame>> {
+    let key = cache_key(oid);
+    let mut cache = Cache::<CustomGame>::load(CUSTOM_GAME_CACHE_FILE, CUSTOM_GAME_CACHE_TTL)?;
+    if !refresh {
+        if let Some(game) = cache.get(&key) {
+            debug!("using cached custom game for oid {oid}");
+            return Ok(Some(game));
+        }
+    }
+
+    let game = get_custom_game(client, oid)?;
+    match &game {
+        Some(game) => cache.insert(key, game.clone()),
+        None => cache.remove(&key),
+    }
+    cache.save()?;
+    Ok(game)
+}
+
+/// Like `fetch_custom_game`, but fails if the user has no custom game
+pub(crate) fn load_custom_game(client: &reqwest::blocking::Client, oid: &str, refresh: bool) -> Result<CustomGame> {
+    fetch_custom_game(client, oid, refresh)?.ok_or(eyre!("This user doesn't have a custom game!"))
+}
+
+/// Removes a custom game from the cache for every account, e.g. after voting on or editing it
+pub(crate) fn forget_custom_game(oid: &str) -> Result<()> {
+    let mut cache = Cache::<CustomGame>::load(CUSTOM_GAME_CACHE_FILE, CUSTOM_GAME_CACHE_TTL)?;
+    let suffix = format!("/{oid}");
+    cache.retain(|key| !key.ends_with(&suffix));
+    cache.save()
+}
+
+/// Returns the current user's custom game, failing with a hint to create one if they don't have one
+fn own_custom_game(client: &reqwest::blocking::Client, account: &AuthInfo) -> Result<CustomGame> {
+    fetch_custom_game(client, &account.user_id, true)?
+        .ok_or(eyre!("You don't have a custom game! Create one with `wbr custom create`."))
+}
+
+/// Prints information about a custom game, including the user's best score on it
+pub(crate) fn print_game_card(client: &reqwest::blocking::Client, game: &CustomGame, oid: &str, authenticated: bool) -> Result<()> {
+    print_attributes(&game.attribute_data);
+    // the creator's handle is only decoration, so don't fail if it can't be loaded
+    if let Ok(creator) = get_profile(client, oid) {
+        println!("{} {}", "Created by:".blue(), format!("@{}", creator.handle).bold());
+    }
+    println!(
+        "{} {} {} {}",
+        game.denormalized_vote_count.to_string().bold().blue(),
+        "likes,".blue(),
+        game.execution_count.to_string().bold().blue(),
+        "plays".blue()
+    );
+
+    match History::load()?.best_custom_score(oid) {
+        Some(score) => println!("{} {}", "Your best score:".blue(), score.to_string().bold().green()),
+        None => println!("{}", "You haven't played this game yet".italic().blue()),
+    }
+    if authenticated {
+        println!("{}", format!("You have {} this game", game.vote_state()).italic().blue());
+    }
+    Ok(())
+}
+
+/// Sets the user's vote on a custom game: `Some(true)` to like, `Some(false)` to downvote, `None` to remove the vote
+fn set_vote(client: &reqwest::blocking::Client, user: &str, vote: Option<bool>) -> Result<()> {
+    let oid = resolve_custom_oid(client, user)?;
+    let game = load_custom_game(client, &oid, false)?;
+
+    let success = match vote {
+        Some(is_upvote) => vote_custom_game(client, &game.id, is_upvote)?,
+        None => remove_custom_game_vote(client, &game.id)?,
+    };
+    if !success {
+        bail!("vote unsuccessful");
+    }
+
+    // fetch the game again to show the updated vote state and like count
+    let game = load_custom_game(client, &oid, true)?;
+    println!(
+        "{} {} {}{}{} {}{}",
+        "You have".green(),
+        game.vote_state().to_string().bold().green(),
+        game.attribute_data.title.bold().green(),
+        " (".green(),
+        game.denormalized_vote_count.to_string().bold().green(),
+        "likes".green(),
+        ")".green()
+    );
+    Ok(())
+}
+
+/// Lists custom games matching a search, and lets the user pick one to play
+fn browse(client: &reqwest::blocking::Client, authenticated: bool, config: &Config, sort: BrowseSort, search: Option<&str>, pages: u64) -> Result<()> {
+    // the API can't search or sort, so do it with the games on as many pages as we're allowed to load
+    let pages = pages.min(MAX_BROWSE_PAGES);
+    let mut games = Vec::<CustomGame>::new();
+    let mut seen = HashSet::new();
+    let mut pages_loaded = 0;
+    for page in 1..=pages {
+        let page_games = list_custom_games(client, page)?;
+        // stop if the API has run out of games, or is giving us games we already have
+        let new_games = page_games.into_iter().filter(|g| seen.insert(g.id.clone())).collect::<Vec<_>>();
+        if new_games.is_empty() {
+            break;
+        }
+        games.extend(new_games);
+        pages_loaded = page;
+    }
+
+    println!("{}", format!("Loaded {} games from {pages_loaded} pages", games.len()).italic().blue());
+    if pages_loaded == pages && pages < MAX_BROWSE_PAGES {
+        println!("{}", "Games on later pages aren't included, use --pages to load more".italic().blue());
+    } else if pages_loaded == MAX_BROWSE_PAGES {
+        println!("{}", format!("Only the first {MAX_BROWSE_PAGES} pages of games are included").italic().blue());
+    }
+
+    if let Some(search) = search {
+        let search = search.to_lowercase();
+        games.retain(|g| g.attribute_data.title.to_lowercase().contains(&search));
+    }
+    games.retain(|g| g.user_id.is_some());
+
+    match sort {
+        BrowseSort::Likes => games.sort_by_key(|g| Reverse(g.denormalized_vote_count)),
+        BrowseSort::Plays => games.sort_by_key(|g| Reverse(g.execution_count)),
+        BrowseSort::Recent => games.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+    }
+
+    if games.is_empty() {
+        println!("{}", "No custom games found".blue());
+        return Ok(());
+    }
+
+    for (n, game) in games.iter().enumerate() {
+        println!(
+            "[{}]: {} {}{} {} {} {}",
+            n + 1,
+            game.attribute_data.title.bold(),
+            "(".dimmed(),
+            game.denormalized_vote_count.to_string().bold(),
+            "likes,".dimmed(),
+            game.execution_count.to_string().bold(),
+            "plays)".dimmed()
+        );
+    }
+
+    let game_number = loop {
+        print!("{}", "Enter game number to play (0 to exit): ".blue());
+        std::io::stdout().flush()?;
+        let mut buf = String::new();
+        std::io::stdin().read_line(&mut buf)?;
+
+        match usize::from_str(buf.trim()) {
+            Ok(n) if n <= games.len() => break n,
+            Ok(_) => println!("{}", format!("Game number must be between 0 and {}", games.len()).red()),
+            Err(_) => println!("{}", "Please enter a valid number!".red()),
+        }
+    };
+
+    if game_number == 0 {
+        return Ok(());
+    }
+    let oid = games[game_number - 1].user_id.clone().unwrap();
+    run_custom(client, authenticated, config, oid, None)
+}
+
+pub(crate) fn run_custom_command(client: &reqwest::blocking::Client, account: Option<&AuthInfo>, config: &Config, command: CustomCommand) -> Result<()> {
+    let require_account = || account.ok_or(eyre!("You need to be logged in to manage custom games!"));
+
+    match command {
+        CustomCommand::Create(options) => {
+            let account = require_account()?;
+            if fetch_custom_game(client, &account.user_id, true)?.is_some() {
+                bail!("You already have a custom game! Use `wbr custom edit` to change it.");
+            }
+            let attributes = read_attributes(options, None)?;
+            print_preview(&attributes);
+            print!("{}", "Create this custom game? [Y/n] ".blue());
+            if read_yes_no_prompt(false)? {
+                if !create_custom_game(client, &attributes)? {
+                    bail!("create custom game unsuccessful");
+                }
+                forget_custom_game(&account.user_id)?;
+                println!("{}", "Created your custom game!".green());
+            }
+        }
+        CustomCommand::Edit(options) => {
+            let account = require_account()?;
+            let game = own_custom_game(client, account)?;
+            let attributes = read_attributes(options, Some(&game.attribute_data))?;
+            print_preview(&attributes);
+            print!("{}", "Save changes? [Y/n] ".blue());
+            if read_yes_no_prompt(false)? {
+                if !update_custom_game(client, &attributes)? {
+                    bail!("update custom game unsuccessful");
+                }
+                forget_custom_game(&account.user_id)?;
+                println!("{}", "Updated your custom game!".green());
+            }
+        }
+        CustomCommand::Like { user } => {
+            require_account()?;
+            set_vote(client, &user, Some(true))?
+        }
+        CustomCommand::Unlike { user } => {
+            require_account()?;
+            set_vote(client, &user, None)?
+        }
+        CustomCommand::Downvote { user } => {
+            require_account()?;
+            set_vote(client, &user, Some(false))?
+        }
+        CustomCommand::Info { user } => {
+            let oid = resolve_custom_oid(client, &user)?;
+            let game = load_custom_game(client, &oid, false)?;
+            print_game_card(client, &game, &oid, account.is_some())?;
+        }
+        CustomCommand::Browse { sort, search, pages } => browse(client, account.is_some(), config, sort, search.as_deref(), pages)?,
+        CustomCommand::Show => {
+            let game = own_custom_game(client, require_account()?)?;
+            print_attributes(&game.attribute_data);
+            println!(
+                "{} {} {} {}",
+                game.denormalized_vote_count.to_string().bold().blue(),
+                "likes,".blue(),
+                game.execution_count.to_string().bold().blue(),
+                "plays".blue()
+            );
+            print_preview(&game.attribute_data);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OID: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+
+    fn oid() -> CustomGameRef {
+        CustomGameRef::Oid(OID.to_string())
+    }
+
+    fn handle(handle: &str) -> CustomGameRef {
+        CustomGameRef::Handle(handle.to_string())
+    }
+
+    #[test]
+    fn parses_handles() {
+        assert_eq!(parse_custom_game_ref("bob").unwrap(), handle("bob"));
+        assert_eq!(parse_custom_game_ref("@bob").unwrap(), handle("bob"));
+        assert_eq!(parse_custom_game_ref("  @bob\n").unwrap(), handle("bob"));
+    }
+
+    #[test]
+    fn parses_bare_uuids() {
+        assert_eq!(parse_custom_game_ref(OID).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref(&OID.to_uppercase()).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref(&OID.replace('-', "")).unwrap(), oid());
+    }
+
+    #[test]
+    fn parses_id_query_links() {
+        assert_eq!(parse_custom_game_ref(&format!("https://www.whatbeatsrock.com/custom?id={OID}")).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref(&format!("https://www.whatbeatsrock.com/custom?oid={OID}")).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref("https://www.whatbeatsrock.com/custom?id=%40bob").unwrap(), handle("bob"));
+    }
+
+    #[test]
+    fn parses_path_links() {
+        assert_eq!(parse_custom_game_ref(&format!("https://www.whatbeatsrock.com/custom/{OID}")).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref(&format!("https://whatbeatsrock.com/custom/{OID}/")).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref("http://www.whatbeatsrock.com/custom/%40bob").unwrap(), handle("bob"));
+    }
+
+    #[test]
+    fn parses_links_without_a_scheme() {
+        assert_eq!(parse_custom_game_ref(&format!("whatbeatsrock.com/custom?id={OID}")).unwrap(), oid());
+        assert_eq!(parse_custom_game_ref(&format!("www.whatbeatsrock.com/custom/{OID}")).unwrap(), oid());
+    }
+
+    #[test]
+    fn rejects_other_sites() {
+        assert!(parse_custom_game_ref(&format!("https://example.com/custom/{OID}")).is_err());
+        assert!(parse_custom_game_ref(&format!("https://notwhatbeatsrock.com/custom/{OID}")).is_err());
+    }
+
+    #[test]
+    fn rejects_links_without_a_game() {
+        assert!(parse_custom_game_ref("https://www.whatbeatsrock.com/").is_err());
+        assert!(parse_custom_game_ref("https://www.whatbeatsrock.com/custom").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use std::io::Write;
+use std::str::FromStr;
+use clap::Subcommand;
+use colored::Colorize;
+use color_eyre::eyre::{bail, Result};
+use crate::api::{get_profile, CustomGameAttributes};
+use crate::custom::{load_custom_game, resolve_custom_oid};
+use crate::save::{load_json, save_json};
+
+const FAVOURITES_FILE: &str = "wbr_favourites.json";
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum FavCommand {
+    /// Add a user's custom game to your favourites
+    Add {
+        /// Handle of the user whose custom game to add, their user ID, or a link to the game
+        user: String,
+    },
+    /// Remove a user's custom game from your favourites
+    Remove {
+        /// Handle of the user whose custom game to remove, their user ID, or a link to the game
+        user: String,
+    },
+    /// List your favourite custom games
+    List,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct Favourite {
+    pub(crate) handle: String,
+    pub(crate) oid: String,
+    /// The game's attributes when it was added, so the list can be shown without fetching every game
+    pub(crate) attributes: CustomGameAttributes,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct Favourites {
+    pub(crate) favourites: Vec<Favourite>,
+}
+
+impl Favourites {
+    pub(crate) fn load() -> Result<Self> {
+        load_json(FAVOURITES_FILE)
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        save_json(FAVOURITES_FILE, self)
+    }
+
+    fn print(&self) {
+        for (n, favourite) in self.favourites.iter().enumerate() {
+            println!("[{}]: {} {}", n + 1, favourite.attributes.title.bold(), format!("by @{}", favourite.handle).italic());
+        }
+    }
+}
+
+pub(crate) fn run_fav_command(client: &reqwest::blocking::Client, command: FavCommand) -> Result<()> {
+    let mut favourites = Favourites::load()?;
+
+    match command {
+        FavCommand::Add { user } => {
+            let oid = resolve_custom_oid(client, &user)?;
+            let game = load_custom_game(client, &oid, false)?;
+            let handle = get_profile(client, &oid)?.handle;
+
+            favourites.favourites.retain(|f| f.oid != oid);
+            favourites.favourites.push(Favourite {
+                handle,
+                oid,
+                attributes: game.attribute_data.clone(),
+            });
+            favourites.save()?;
+            println!("{} {}", "Added to favourites:".green(), game.attribute_data.title.bold().green());
+        }
+        FavCommand::Remove { user } => {
+            let handle = user.trim().strip_prefix('@').unwrap_or(user.trim());
+            // favourites can be removed by the handle they were saved with, without going online
+            let index = match favourites.favourites.iter().position(|f| f.handle.eq_ignore_ascii_case(handle) || f.oid == handle) {
+                Some(index) => index,
+                None => {
+                    let oid = resolve_custom_oid(client, &user)?;
+                    match favourites.favourites.iter().position(|f| f.oid == oid) {
+                        Some(index) => index,
+                        None => bail!("{user}'s custom game is not in your favourites"),
+                    }
+                }
+            };
+            let favourite = favourites.favourites.remove(index);
+            favourites.save()?;
+            println!("{}", format!("Removed @{}'s custom game from favourites", favourite.handle).green());
+        }
+        FavCommand::List => {
+            if favourites.favourites.is_empty() {
+                println!("{}", "You have no favourite custom games! Add one with `wbr fav add @<username>`.".blue());
+            }
+            favourites.print();
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks the user whether to play one of their favourite custom games, returning its oid if they pick one
+pub(crate) fn pick_favourite() -> Result<Option<String>> {
+    let favourites = Favourites::load()?;
+    if favourites.favourites.is_empty() {
+        return Ok(None);
+    }
+
+    println!("{}", "Favourite custom games:".blue());
+    favourites.print();
+
+    let number = loop {
+        print!("{}", "Enter game number to play (0 or leave empty for a normal game): ".blue());
+        std::io::stdout().flush()?;
+        let mut buf = String::new();
+        std::io::stdin().read_line(&mut buf)?;
+        let buf = buf.trim();
+        if buf.is_empty() {
+            break 0;
+        }
+
+        match usize::from_str(buf) {
+            Ok(n) if n <= favourites.favourites.len() => break n,
+            Ok(_) => println!("{}", format!("Game number must be between 0 and {}", favourites.favourites.len()).red()),
+            Err(_) => println!("{}", "Please enter a valid number!".red()),
+        }
+    };
+
+    Ok(number.checked_sub(1).map(|n| favourites.favourites[n].oid.clone()))
+}
@@ -0,0 +1,87 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Longest guess that can be sent, in characters
+const MAX_GUESS_LENGTH: usize = 100;
+
+/// Normalises a guess to NFC with single spaces between words, and checks that it can be sent
+pub(crate) fn normalise_guess(guess: &str) -> Result<String, String> {
+    let guess = guess.nfc().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+    if guess.is_empty() {
+        return Err("Please enter a guess!".to_string());
+    }
+    if guess.chars().any(char::is_control) {
+        return Err("Guesses can't contain control characters!".to_string());
+    }
+    if guess.chars().count() > MAX_GUESS_LENGTH {
+        return Err(format!("Guesses can't be longer than {MAX_GUESS_LENGTH} characters!"));
+    }
+    Ok(guess)
+}
+
+/// Whether two guesses count as the same word. What Beats Rock ignores case, so guesses are compared in lowercase.
+pub(crate) fn same_guess(a: &str, b: &str) -> bool {
+    let fold = |guess: &str| normalise_guess(guess).unwrap_or_default().to_lowercase();
+    fold(a) == fold(b)
+}
+
+/// Whether a guess has already been used in a chain
+pub(crate) fn in_chain(chain: &[String], guess: &str) -> bool {
+    chain.iter().any(|word| same_guess(word, guess))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalises_to_nfc() {
+        // "e" followed by a combining acute accent
+        assert_eq!(normalise_guess("cafe\u{301}").unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        assert_eq!(normalise_guess("  a   big\t\nrock ").unwrap(), "a big rock");
+    }
+
+    #[test]
+    fn rejects_empty_guesses() {
+        assert!(normalise_guess("").is_err());
+        assert!(normalise_guess(" \t\n").is_err());
+    }
+
+    #[test]
+    fn rejects_control_characters() {
+        assert!(normalise_guess("rock\u{7}").is_err());
+    }
+
+    #[test]
+    fn limits_length_in_characters() {
+        assert!(normalise_guess(&"a".repeat(MAX_GUESS_LENGTH)).is_ok());
+        assert!(normalise_guess(&"a".repeat(MAX_GUESS_LENGTH + 1)).is_err());
+        // multi-byte characters count once each
+        assert!(normalise_guess(&"é".repeat(MAX_GUESS_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn length_is_checked_after_collapsing_whitespace() {
+        let guess = format!("{}     {}", "a".repeat(49), "b".repeat(49));
+        assert!(normalise_guess(&guess).is_ok());
+    }
+
+    #[test]
+    fn same_guess_ignores_case_and_spacing() {
+        assert!(same_guess("Paper", "paper"));
+        assert!(same_guess("BIG  rock", "big rock"));
+        assert!(same_guess("CAFE\u{301}", "caf\u{e9}"));
+        assert!(!same_guess("paper", "papers"));
+    }
+
+    #[test]
+    fn in_chain_finds_repeats_regardless_of_case() {
+        let chain = vec!["rock".to_string(), "Paper".to_string()];
+        assert!(in_chain(&chain, "PAPER"));
+        assert!(in_chain(&chain, " rock "));
+        assert!(!in_chain(&chain, "scissors"));
+    }
+}
@@ -0,0 +1,50 @@
+use color_eyre::Result;
+use crate::cache::now;
+use crate::save::{load_json, save_json};
+
+const HISTORY_FILE: &str = "wbr_history.json";
+
+/// A finished game
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct GameRecord {
+    pub(crate) is_custom: bool,
+    pub(crate) gid_oid: String,
+    pub(crate) score: u64,
+    pub(crate) finished_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct History {
+    pub(crate) games: Vec<GameRecord>,
+}
+
+impl History {
+    pub(crate) fn load() -> Result<Self> {
+        load_json(HISTORY_FILE)
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        save_json(HISTORY_FILE, self)
+    }
+
+    /// Adds a finished game to the history file
+    pub(crate) fn record_game(is_custom: bool, gid_oid: &str, score: u64) -> Result<()> {
+        let mut history = Self::load()?;
+        history.games.push(GameRecord {
+            is_custom,
+            gid_oid: gid_oid.to_string(),
+            score,
+            finished_at: now(),
+        });
+        history.save()
+    }
+
+    /// Returns the best score the user has got on a custom game
+    pub(crate) fn best_custom_score(&self, oid: &str) -> Option<u64> {
+        self.games
+            .iter()
+            .filter(|g| g.is_custom && g.gid_oid == oid)
+            .map(|g| g.score)
+            .max()
+    }
+}
@@ -0,0 +1,284 @@
+use std::path::Path;
+use clap::ValueEnum;
+use color_eyre::eyre::{bail, eyre, Result};
+use log::debug;
+use crate::api::{do_custom_guess, do_guess, CustomGameRequest, GameRequest, GameResponseInner};
+use crate::config::Config;
+use crate::matchups::Matchups;
+
+const LLM_API_KEY_VAR: &str = "WBR_LLM_API_KEY";
+const DEFAULT_LLM_MODEL: &str = "local";
+const LLM_SYSTEM_PROMPT: &str = "You are the judge of a word game. The player names something, and you decide whether it \
+beats the previous thing, using common sense and a sense of humour. Answer with only a JSON object of the form \
+{\"wins\": true, \"emoji\": \"<one emoji for the player's guess>\", \"reason\": \"<one short sentence explaining why>\"}.";
+
+#[derive(ValueEnum, serde::Serialize, serde::Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JudgeBackend {
+    /// Judge guesses with the What Beats Rock API
+    #[default]
+    Remote,
+    /// Judge guesses with a local rule file
+    Rules,
+    /// Judge guesses with an OpenAI-compatible chat completions API, e.g. a llama.cpp server
+    Llm,
+}
+
+/// Decides whether guesses win
+pub(crate) trait Judge {
+    fn judge(&self, guess: &str, prev_guess: &str) -> Result<GameResponseInner>;
+
+    /// Whether the results come from What Beats Rock, so scores can go on the public leaderboard
+    fn is_official(&self) -> bool {
+        false
+    }
+}
+
+/// Remembers a judged guess. The guess has already been judged, so failing to save it isn't worth losing the result over.
+fn record_matchup(oid: Option<&str>, prev_guess: &str, guess: &str, response: &GameResponseInner) {
+    if let Err(e) = Matchups::record(oid, prev_guess, guess, response) {
+        debug!("could not record matchup {guess} vs {prev_guess}: {e}");
+    }
+}
+
+/// Judges guesses with the What Beats Rock API
+pub(crate) struct RemoteJudge<'a> {
+    client: &'a reqwest::blocking::Client,
+    is_custom: bool,
+    gid_oid: String,
+}
+
+impl<'a> RemoteJudge<'a> {
+    pub(crate) fn new(client: &'a reqwest::blocking::Client, is_custom: bool, gid_oid: String) -> Self {
+        Self { client, is_custom, gid_oid }
+    }
+}
+
+impl Judge for RemoteJudge<'_> {
+    fn judge(&self, guess: &str, prev_guess: &str) -> Result<GameResponseInner> {
+        if self.is_custom {
+            let request = CustomGameRequest {
+                oid: self.gid_oid.clone(),
+                guess: guess.to_string(),
+                prev: prev_guess.to_string(),
+            };
+            let response = do_custom_guess(self.client, request)?;
+            record_matchup(Some(&self.gid_oid), prev_guess, guess, &response);
+            Ok(response)
+        } else {
+            let request = GameRequest {
+                gid: self.gid_oid.clone(),
+                guess: guess.to_string(),
+                prev: prev_guess.to_string(),
+            };
+            let response = do_guess(self.client, request)?;
+            record_matchup(None, prev_guess, guess, &response);
+            Ok(response)
+        }
+    }
+
+    fn is_official(&self) -> bool {
+        true
+    }
+}
+
+/// Judges normal game guesses using their results from What Beats Rock, so games can be practised offline
+pub(crate) struct PracticeJudge {
+    matchups: Matchups,
+}
+
+impl PracticeJudge {
+    pub(crate) fn load() -> Result<Self> {
+        Ok(Self { matchups: Matchups::load()? })
+    }
+
+    /// Whether there are no winning guesses to practise with
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.matchups.matchups.iter().any(|m| m.oid.is_none() && m.wins)
+    }
+}
+
+impl Judge for PracticeJudge {
+    fn judge(&self, guess: &str, prev_guess: &str) -> Result<GameResponseInner> {
+        Ok(match self.matchups.find(None, prev_guess, guess) {
+            Some(matchup) => matchup.response(),
+            None => GameResponseInner {
+                guess_wins: false,
+                guess_emoji: String::new(),
+                reason: format!("{guess} vs {prev_guess} hasn't been judged before."),
+                cache_count: None,
+            },
+        })
+    }
+}
+
+/// A rule in a rule file. `*` matches any guess.
+#[derive(serde::Deserialize, Clone, Debug)]
+struct Rule {
+    guess: String,
+    prev: String,
+    /// Whether the guess beats the previous guess, defaults to true
+    wins: Option<bool>,
+    emoji: Option<String>,
+    reason: Option<String>,
+}
+
+impl Rule {
+    fn matches(&self, guess: &str, prev_guess: &str) -> bool {
+        let matches = |pattern: &str, word: &str| pattern == "*" || pattern.eq_ignore_ascii_case(word);
+        matches(&self.guess, guess) && matches(&self.prev, prev_guess)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+struct RuleFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+/// Judges guesses with the first matching rule from a TOML rule file. Guesses that no rule matches lose.
+pub(crate) struct RuleJudge {
+    rules: Vec<Rule>,
+}
+
+impl RuleJudge {
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let toml = std::fs::read_to_string(path).map_err(|e| eyre!("Could not read rule file {}: {e}", path.display()))?;
+        let rule_file = toml::from_str::<RuleFile>(&toml)?;
+        debug!("loaded {} rules from {}", rule_file.rules.len(), path.display());
+        Ok(Self { rules: rule_file.rules })
+    }
+}
+
+impl Judge for RuleJudge {
+    fn judge(&self, guess: &str, prev_guess: &str) -> Result<GameResponseInner> {
+        Ok(match self.rules.iter().find(|r| r.matches(guess, prev_guess)) {
+            Some(rule) => GameResponseInner {
+                guess_wins: rule.wins.unwrap_or(true),
+                guess_emoji: rule.emoji.clone().unwrap_or_default(),
+                reason: rule.reason.clone().unwrap_or_default(),
+                cache_count: None,
+            },
+            None => GameResponseInner {
+                guess_wins: false,
+                guess_emoji: String::new(),
+                reason: format!("No rule says {guess} beats {prev_guess}."),
+                cache_count: None,
+            },
+        })
+    }
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    temperature: f64,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct LlmVerdict {
+    wins: bool,
+    #[serde(default)]
+    emoji: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Judges guesses with an OpenAI-compatible chat completions API
+pub(crate) struct LlmJudge<'a> {
+    client: &'a reqwest::blocking::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    judging_criteria: String,
+}
+
+impl<'a> LlmJudge<'a> {
+    /// `url` is the base URL of the API, e.g. `http://localhost:8080/v1/`
+    pub(crate) fn new(client: &'a reqwest::blocking::Client, url: &str, model: &str, judging_criteria: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/chat/completions", url.trim_end_matches('/')),
+            model: model.to_string(),
+            api_key: std::env::var(LLM_API_KEY_VAR).ok(),
+            judging_criteria: judging_criteria.to_string(),
+        }
+    }
+}
+
+impl Judge for LlmJudge<'_> {
+    fn judge(&self, guess: &str, prev_guess: &str) -> Result<GameResponseInner> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ChatMessage { role: "system", content: LLM_SYSTEM_PROMPT.to_string() },
+                ChatMessage { role: "user", content: format!("Does \"{guess}\" {} \"{prev_guess}\"?", self.judging_criteria) },
+            ],
+            temperature: 0.0,
+        };
+        let json = serde_json::to_string(&request)?;
+        debug!("request POST {} {json}", self.url);
+
+        let mut builder = self.client.post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(json);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let text = builder.send()?.error_for_status()?.text()?;
+        debug!("response {text}");
+
+        let response = serde_json::from_str::<ChatResponse>(&text)?;
+        let content = response.choices.first().map(|c| c.message.content.as_str()).unwrap_or_default();
+        // models like to wrap JSON in code fences or add commentary, so just take the object
+        let verdict = content.find('{')
+            .zip(content.rfind('}'))
+            .and_then(|(start, end)| content.get(start..=end))
+            .ok_or(eyre!("Model did not give a verdict: {content}"))?;
+        let verdict = serde_json::from_str::<LlmVerdict>(verdict)?;
+
+        Ok(GameResponseInner {
+            guess_wins: verdict.wins,
+            guess_emoji: verdict.emoji,
+            reason: verdict.reason,
+            cache_count: None,
+        })
+    }
+}
+
+/// Creates the judge selected in the config for a game
+pub(crate) fn create_judge<'a>(client: &'a reqwest::blocking::Client, config: &Config, is_custom: bool, gid_oid: &str, judging_criteria: &str) -> Result<Box<dyn Judge + 'a>> {
+    Ok(match config.judge.unwrap_or_default() {
+        JudgeBackend::Remote => Box::new(RemoteJudge::new(client, is_custom, gid_oid.to_string())),
+        JudgeBackend::Rules => match &config.rules_file {
+            Some(path) => Box::new(RuleJudge::load(path)?),
+            None => bail!("No rule file set! Use --rules-file or `wbr config set rules_file <path>`."),
+        },
+        JudgeBackend::Llm => match &config.llm_url {
+            Some(url) => Box::new(LlmJudge::new(client, url, config.llm_model.as_deref().unwrap_or(DEFAULT_LLM_MODEL), judging_criteria)),
+            None => bail!("No LLM API URL set! Use --llm-url or `wbr config set llm_url <url>`."),
+        },
+    })
+}
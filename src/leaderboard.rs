@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use colored::Colorize;
+use color_eyre::Result;
+use crate::api::LeaderboardRequest;
+use crate::save::{load_json, save_json};
+
+const LOCAL_LEADERBOARD_FILE: &str = "wbr_leaderboard.json";
+
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LeaderboardBackend {
+    /// Submit scores to the public whatbeatsrock.com leaderboard
+    #[default]
+    Remote,
+    /// Store scores in a leaderboard file on this computer
+    Local,
+}
+
+/// Checks that leaderboard initials are 3 letters or numbers, and converts them to uppercase
+pub(crate) fn normalise_initials(initials: &str) -> Result<String, &'static str> {
+    if initials.chars().count() != 3 {
+        return Err("Initials must be exactly 3 characters!");
+    }
+    if !initials.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Initials can only contain letters A-Z and numbers 0-9!");
+    }
+    Ok(initials.to_ascii_uppercase())
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct LocalLeaderboard {
+    pub(crate) entries: Vec<LeaderboardRequest>,
+}
+
+impl LocalLeaderboard {
+    pub(crate) fn load() -> Result<Self> {
+        load_json(LOCAL_LEADERBOARD_FILE)
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        save_json(LOCAL_LEADERBOARD_FILE, self)
+    }
+
+    /// Adds a score to the local leaderboard file
+    pub(crate) fn submit(request: LeaderboardRequest) -> Result<()> {
+        let mut leaderboard = Self::load()?;
+        leaderboard.entries.push(request);
+        leaderboard.save()
+    }
+
+    /// Returns all entries, highest score first
+    pub(crate) fn rankings(&self) -> Vec<&LeaderboardRequest> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        entries
+    }
+
+    /// Returns the best entry for each player, highest score first
+    pub(crate) fn personal_bests(&self) -> Vec<&LeaderboardRequest> {
+        let mut bests: HashMap<&str, &LeaderboardRequest> = HashMap::new();
+        for entry in &self.entries {
+            let best = bests.entry(&entry.initials).or_insert(entry);
+            if entry.score > best.score {
+                *best = entry;
+            }
+        }
+        let mut bests = bests.into_values().collect::<Vec<_>>();
+        bests.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.initials.cmp(&b.initials)));
+        bests
+    }
+}
+
+fn print_entries(entries: &[&LeaderboardRequest]) {
+    for (n, entry) in entries.iter().enumerate() {
+        println!("{:>3}. {} {} {}", n + 1, entry.initials.bold(), entry.score.to_string().bold().green(), entry.text.italic());
+    }
+}
+
+pub(crate) fn show_leaderboard(local: bool) -> Result<()> {
+    if !local {
+        println!("{}", "View the public leaderboard at https://www.whatbeatsrock.com/leaderboard, or use --local to view the local leaderboard".blue());
+        return Ok(());
+    }
+
+    let leaderboard = LocalLeaderboard::load()?;
+    if leaderboard.entries.is_empty() {
+        println!("{}", "The local leaderboard is empty! Play with --leaderboard local to add scores to it.".blue());
+        return Ok(());
+    }
+
+    println!("{}", "Rankings".bold().blue());
+    print_entries(&leaderboard.rankings());
+    println!();
+    println!("{}", "Personal bests".bold().blue());
+    print_entries(&leaderboard.personal_bests());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_letters_and_numbers() {
+        assert_eq!(normalise_initials("ABC").unwrap(), "ABC");
+        assert_eq!(normalise_initials("A1B").unwrap(), "A1B");
+        assert_eq!(normalise_initials("007").unwrap(), "007");
+    }
+
+    #[test]
+    fn uppercases_initials() {
+        assert_eq!(normalise_initials("abc").unwrap(), "ABC");
+        assert_eq!(normalise_initials("aB9").unwrap(), "AB9");
+    }
+
+    #[test]
+    fn requires_exactly_3_characters() {
+        assert!(normalise_initials("").is_err());
+        assert!(normalise_initials("AB").is_err());
+        assert!(normalise_initials("ABCD").is_err());
+    }
+
+    #[test]
+    fn rejects_non_ascii_initials() {
+        // 3 characters, but more than 3 bytes
+        assert!(normalise_initials("ÉCU").is_err());
+        assert!(normalise_initials("ééé").is_err());
+        assert!(normalise_initials("ＡＢＣ").is_err());
+    }
+
+    #[test]
+    fn rejects_symbols_and_spaces() {
+        assert!(normalise_initials("A B").is_err());
+        assert!(normalise_initials("A-B").is_err());
+        assert!(normalise_initials(" AB").is_err());
+    }
+}
@@ -1,23 +1,153 @@
 mod auth;
 mod api;
 mod save;
+mod leaderboard;
+mod outbox;
+mod config;
+mod browser;
+mod cache;
+mod profile;
+mod custom;
+mod history;
+mod fav;
+mod judge;
+mod matchups;
+mod bot;
+mod guess;
+mod party;
 
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::Arc;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use color_eyre::eyre::Result;
 use color_eyre::owo_colors::OwoColorize;
 use log::{debug, LevelFilter};
-use crate::api::{do_guess, submit_score, submit_score_authenticated, AuthenticatedLeaderboardRequest, LeaderboardRequest, GameRequest, GameResponseInner, get_custom_game, CustomGameRequest, do_custom_guess, like_custom_game};
-use crate::auth::{add_auth_cookie, auth_prompt, get_session_cookies, get_user_id};
+use crate::api::{AuthenticatedLeaderboardRequest, LeaderboardRequest, vote_custom_game, set_api_base, VoteState};
+use crate::bot::{run_bot, BotArgs};
+use crate::browser::Browser;
+use crate::config::{run_config_command, Config, ConfigCommand};
+use crate::auth::{auth_prompt, get_session_cookies, get_stored_session, get_token_account, list_accounts, login, logout, set_active_session};
+use crate::leaderboard::{normalise_initials, show_leaderboard, LeaderboardBackend, LocalLeaderboard};
+use crate::profile::show_profile;
+use crate::custom::{forget_custom_game, load_custom_game, print_game_card, resolve_custom_oid, run_custom_command, CustomCommand};
+use crate::history::History;
+use crate::guess::{in_chain, normalise_guess};
+use crate::fav::{pick_favourite, run_fav_command, FavCommand};
+use crate::judge::{create_judge, Judge, JudgeBackend, PracticeJudge};
+use crate::matchups::{print_suggestions, show_lookup};
+use crate::party::{run_party, PartyArgs};
+use crate::outbox::{submit_or_queue, submit_pending, PendingSubmission, SubmitOutcome};
 use crate::save::SaveData;
 
 #[derive(Parser, Debug)]
 #[command(version)]
 struct Args {
-    #[arg(short, long)]
-    custom_username: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Play a custom game, given the creator's handle, their user ID, or a link to the game
+    #[arg(short, long, visible_alias = "custom-username")]
+    custom: Option<String>,
+
+    /// Where to submit leaderboard scores
+    #[arg(long, value_enum)]
+    leaderboard: Option<LeaderboardBackend>,
+
+    /// Value of the sb-...-auth-token cookie to log in with
+    #[arg(long, env = "WBR_AUTH_TOKEN", hide_env_values = true)]
+    auth_token: Option<String>,
+
+    /// File containing the value of the sb-...-auth-token cookie to log in with (takes precedence over --auth-token)
+    #[arg(long)]
+    auth_token_file: Option<PathBuf>,
+
+    /// Only load cookies from this browser
+    #[arg(long, value_enum, global = true)]
+    browser: Option<Browser>,
+
+    /// Only load cookies from this browser profile directory or cookie database. Use with --browser to say which browser it belongs to.
+    #[arg(long, global = true)]
+    browser_profile: Option<PathBuf>,
+
+    /// How to judge guesses
+    #[arg(long, value_enum, global = true)]
+    judge: Option<JudgeBackend>,
+
+    /// Rule file to judge guesses with, for `--judge rules`
+    #[arg(long, global = true)]
+    rules_file: Option<PathBuf>,
+
+    /// Base URL of an OpenAI-compatible API to judge guesses with, for `--judge llm`
+    #[arg(long, global = true)]
+    llm_url: Option<String>,
+
+    /// Model to judge guesses with, for `--judge llm`
+    #[arg(long, global = true)]
+    llm_model: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// View the leaderboard
+    Leaderboard {
+        /// Show the local leaderboard rankings and personal bests
+        #[arg(long)]
+        local: bool,
+    },
+    /// Retry leaderboard submissions that previously failed
+    SubmitPending,
+    /// List logged in accounts and where they were found
+    Accounts,
+    /// Log in to an account, without needing a browser
+    Login,
+    /// Log out of the account stored by `wbr login`
+    Logout,
+    /// Show a user's public profile
+    Profile {
+        /// Handle of the user, defaults to the current account
+        handle: Option<String>,
+    },
+    /// Manage your custom game, or vote on others
+    Custom {
+        #[command(subcommand)]
+        command: CustomCommand,
+    },
+    /// Manage your favourite custom games
+    Fav {
+        #[command(subcommand)]
+        command: FavCommand,
+    },
+    /// Practise offline, replaying the results of guesses that have been judged before
+    Practice,
+    /// List guesses that have beaten a word before
+    Suggest {
+        /// The word to beat
+        word: String,
+        /// Suggest guesses for a custom game instead, given the creator's handle, their user ID, or a link to the game
+        #[arg(long)]
+        custom: Option<String>,
+    },
+    /// Play games automatically
+    Bot(BotArgs),
+    /// Play with friends, taking turns on one computer
+    Party(PartyArgs),
+    /// Show whether a guess has been judged before
+    Lookup {
+        /// The word being beaten
+        prev: String,
+        /// The guess
+        guess: String,
+        /// Look up a guess in a custom game instead, given the creator's handle, their user ID, or a link to the game
+        #[arg(long)]
+        custom: Option<String>,
+    },
+    /// View or change default settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
 }
 
 fn read_yes_no_prompt(default_no: bool) -> Result<bool> {
@@ -37,40 +167,91 @@ struct GameResult {
     emoji: String,
     prev_guess: String,
     prev_emoji: String,
+    /// Whether the player left with EXIT instead of losing. Only games without a save template return when exited,
+    /// otherwise the process exits after offering to save.
+    exited: bool,
+}
+
+pub(crate) fn print_prompt(judging_criteria_win: &str, prev_guess: &str, prev_emoji: &str) {
+    print!("{} {} {} {}{} ", "What".blue(), judging_criteria_win.blue(), prev_guess.bold().blue(), prev_emoji.bold().blue(), "?".blue());
+}
+
+pub(crate) fn print_win(guess: &str, emoji: &str, judging_criteria_win: &str, prev_guess: &str, prev_emoji: &str) {
+    println!("{} {} {} {} {}{}", guess.bold().green(), emoji.bold().green(), judging_criteria_win.green(), prev_guess.bold().green(), prev_emoji.bold().green(), "!".green());
+}
+
+pub(crate) fn print_loss(guess: &str, emoji: &str, judging_criteria_loss: &str, prev_guess: &str, prev_emoji: &str) {
+    println!("{} {} {} {} {}{}", guess.bold().red(), emoji.bold().red(), judging_criteria_loss.red(), prev_guess.bold().red(), prev_emoji.bold().red(), "!".red());
 }
 
-fn do_game(is_custom: bool, start_guess: &str, start_emoji: &str, judging_criteria_win: &str, judging_criteria_loss: &str, mut save_template: SaveData, callback: impl Fn(&str, &str) -> Result<GameResponseInner>) -> Result<GameResult> {
+/// Plays a game until the player loses. `oid` is the custom game being played, or `None` for a normal game.
+fn do_game(oid: Option<&str>, start_guess: &str, start_emoji: &str, judging_criteria_win: &str, judging_criteria_loss: &str, mut save_template: Option<SaveData>, judge: &dyn Judge) -> Result<GameResult> {
     let mut count: u64 = 0;
     let mut prev_guess = start_guess.to_string();
     let mut prev_emoji = start_emoji.to_string();
+    // when resuming, the template holds the words used before the save
+    let mut chain = save_template.as_ref().map(|s| s.chain.clone()).unwrap_or_default();
+    if !in_chain(&chain, start_guess) {
+        chain.push(start_guess.to_string());
+    }
 
     loop {
         let (guess, response) = loop {
-            print!("{} {} {} {}{} ", "What".blue(), judging_criteria_win.blue(), prev_guess.bold().blue(), prev_emoji.bold().blue(), "?".blue());
+            print_prompt(judging_criteria_win, &prev_guess, &prev_emoji);
             std::io::stdout().flush()?;
             let mut guess = String::new();
             std::io::stdin().read_line(&mut guess)?;
             guess = guess.trim().to_string();
 
+            if guess == "/hint" {
+                print_suggestions(oid, &prev_guess)?;
+                continue;
+            }
+
             if guess == "EXIT" {
+                let Some(save_template) = &mut save_template else {
+                    // games that can't be saved are part of something bigger, so let the caller decide what to do
+                    return Ok(GameResult {
+                        score: count,
+                        guess: String::new(),
+                        emoji: String::new(),
+                        prev_guess,
+                        prev_emoji,
+                        exited: true,
+                    });
+                };
                 print!("{}", "Save game? [y/N] ".blue());
                 if read_yes_no_prompt(true)? {
-                    save_template.update(prev_guess, prev_emoji, count);
+                    save_template.update(prev_guess, prev_emoji, count, chain);
                     save_template.save()?;
                 }
                 std::process::exit(0);
             }
 
-            match callback(&guess, &prev_guess) {
+            let guess = match normalise_guess(&guess) {
+                Ok(guess) => guess,
+                Err(e) => {
+                    println!("{}", e.red());
+                    continue;
+                }
+            };
+
+            // What Beats Rock doesn't allow a word to be used twice in the same game
+            if in_chain(&chain, &guess) {
+                println!("{}", format!("{guess} has already been used in this game! Each word can only be used once, so try something else.").red());
+                continue;
+            }
+
+            match judge.judge(&guess, &prev_guess) {
                 Ok(response) => break (guess, response),
                 Err(e) => eprintln!("{} {}", "API error:".red(), e.to_string().red()),
             };
         };
 
         if response.guess_wins {
-            println!("{} {} {} {} {}{}", guess.bold().green(), response.guess_emoji.bold().green(), judging_criteria_win.green(), prev_guess.bold().green(), prev_emoji.bold().green(), "!".green());
+            print_win(&guess, &response.guess_emoji, judging_criteria_win, &prev_guess, &prev_emoji);
             println!("{}", response.reason.green());
-            if !is_custom {
+            if oid.is_none() && judge.is_official() {
                 if let Some(n) = response.cache_count {
                     println!("{} {}", n.to_string().bold().green(), "others guessed this too!".green());
                 } else {
@@ -79,81 +260,141 @@ fn do_game(is_custom: bool, start_guess: &str, start_emoji: &str, judging_criter
             }
             count += 1;
         } else {
-            println!("{} {} {} {} {}{}", guess.bold().red(), response.guess_emoji.bold().red(), judging_criteria_loss.red(), prev_guess.bold().red(), prev_emoji.bold().red(), "!".red());
+            print_loss(&guess, &response.guess_emoji, judging_criteria_loss, &prev_guess, &prev_emoji);
             println!("{}", response.reason.red());
             println!("{} {} {}", "You made".blue(), count.to_string().bold().blue(), "correct guesses".blue());
+            if let Some(save_template) = &save_template {
+                // the score still needs submitting, so don't lose it over the history file
+                if let Err(e) = History::record_game(save_template.is_custom, &save_template.gid_oid, count) {
+                    eprintln!("{} {}", "Could not save game to history:".red(), e.to_string().red());
+                }
+            }
             break Ok(GameResult {
                 score: count,
                 guess,
                 emoji: response.guess_emoji,
                 prev_guess,
-                prev_emoji
+                prev_emoji,
+                exited: false,
             });
         }
 
+        chain.push(guess.clone());
         prev_guess = guess;
         prev_emoji = response.guess_emoji;
     }
 }
 
-fn run_normal(client: &reqwest::blocking::Client, authenticated: bool, mut save_data: Option<SaveData>) -> Result<()> {
+/// Reads leaderboard initials, returning `None` if the player wants to skip submitting.
+///
+/// The default initials from the config are used unless `use_default` is false.
+fn read_initials(config: &Config, use_default: bool) -> Result<Option<String>> {
+    if let Some(initials) = config.initials.as_ref().filter(|_| use_default) {
+        return Ok(Some(initials.clone()));
+    }
+
+    let mut buf = String::new();
+    loop {
+        print!("{}", "Enter leaderboard initials (3 letters or numbers, leave empty to skip): ".blue());
+        std::io::stdout().flush()?;
+        buf.clear();
+        std::io::stdin().read_line(&mut buf)?;
+        let input = buf.trim();
+        if input.is_empty() {
+            break Ok(None);
+        }
+        match normalise_initials(input) {
+            Ok(initials) => break Ok(Some(initials)),
+            Err(e) => println!("{}", e.red()),
+        }
+    }
+}
+
+fn submit_result(client: &reqwest::blocking::Client, uid: Option<&str>, config: &Config, gid: &str, result: &GameResult) -> Result<()> {
+    let text = format!("{} {} did not beat {} {}", result.guess, result.emoji, result.prev_guess, result.prev_emoji);
+
+    match (config.leaderboard.unwrap_or_default(), uid) {
+        (LeaderboardBackend::Local, _) => {
+            if let Some(initials) = read_initials(config, true)? {
+                let leaderboard_request = LeaderboardRequest {
+                    gid: gid.to_string(),
+                    initials,
+                    score: result.score,
+                    text,
+                };
+                LocalLeaderboard::submit(leaderboard_request)?;
+            }
+        }
+        (LeaderboardBackend::Remote, Some(uid)) => {
+            let leaderboard_request = AuthenticatedLeaderboardRequest {
+                gid: gid.to_string(),
+                score: result.score,
+                text,
+            };
+            let submission = PendingSubmission::Authenticated { user_id: uid.to_string(), request: leaderboard_request };
+            if let SubmitOutcome::Rejected { reason, .. } = submit_or_queue(client, submission)? {
+                println!("{} {}", "Leaderboard rejected score:".red(), reason.red());
+            }
+        }
+        (LeaderboardBackend::Remote, None) => {
+            let mut use_default = true;
+            while let Some(initials) = read_initials(config, use_default)? {
+                let leaderboard_request = LeaderboardRequest {
+                    gid: gid.to_string(),
+                    initials,
+                    score: result.score,
+                    text: text.clone(),
+                };
+                match submit_or_queue(client, PendingSubmission::Anonymous(leaderboard_request))? {
+                    // only ask for initials again if they were the problem
+                    SubmitOutcome::Rejected { reason, bad_initials: true } => {
+                        println!("{} {}", "Leaderboard rejected initials:".red(), reason.red());
+                        use_default = false;
+                    }
+                    SubmitOutcome::Rejected { reason, bad_initials: false } => {
+                        println!("{} {}", "Leaderboard rejected score:".red(), reason.red());
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_normal(client: &reqwest::blocking::Client, uid: Option<&str>, config: &Config, mut save_data: Option<SaveData>) -> Result<()> {
     let mut gid = save_data.as_ref().map(|d| d.gid_oid.clone()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
     debug!("gid {gid}");
 
     loop {
-        let save_template = SaveData::template(false, gid.clone());
+        let mut save_template = SaveData::template(false, gid.clone());
+        save_template.chain = save_data.as_ref().map(|d| d.chain.clone()).unwrap_or_default();
+        let judge = create_judge(client, config, false, &gid, "beats")?;
 
         let result = do_game(
-            false,
+            None,
             save_data.as_ref().map(|d| d.prev_guess.as_ref()).unwrap_or("rock"),
             save_data.as_ref().map(|d| d.prev_emoji.as_ref()).unwrap_or("🪨"),
             "beats",
             "does not beat",
-            save_template,
-            |guess, prev_guess| {
-                let request = GameRequest {
-                    gid: gid.clone(),
-                    guess: guess.to_string(),
-                    prev: prev_guess.to_string(),
-                };
-                do_guess(&client, request)
-            }
+            Some(save_template),
+            judge.as_ref(),
         )?;
 
-        print!("{}", "Would you like to submit to the leaderboard? [y/N] ".blue());
-        if read_yes_no_prompt(true)? {
-            if authenticated {
-                let leaderboard_request = AuthenticatedLeaderboardRequest {
-                    gid: gid.clone(),
-                    score: result.score,
-                    text: format!("{} {} did not beat {} {}", result.guess, result.emoji, result.prev_guess, result.prev_emoji),
-                };
-                if !submit_score_authenticated(&client, leaderboard_request)? {
-                    println!("{}", "submit score unsuccessful".red());
-                }
-            } else {
-                let mut buf = String::new();
-                let initials = loop {
-                    print!("{}", "Enter leaderboard initials (3 characters): ".blue());
-                    std::io::stdout().flush()?;
-                    buf.clear();
-                    std::io::stdin().read_line(&mut buf)?;
-                    let buf = buf.trim().to_string();
-                    if buf.chars().count() == 3 {
-                        break buf;
-                    }
-                    print!("{}", "Must be 3 characters!".red());
-                };
-
-                let leaderboard_request = LeaderboardRequest {
-                    gid: gid.clone(),
-                    initials,
-                    score: result.score,
-                    text: format!("{} {} did not beat {} {}", result.guess, result.emoji, result.prev_guess, result.prev_emoji),
-                };
-                if !submit_score(&client, leaderboard_request)? {
-                    println!("{}", "submit score unsuccessful".red());
+        if !judge.is_official() && config.leaderboard.unwrap_or_default() == LeaderboardBackend::Remote {
+            println!("{}", "Games judged locally can only be submitted to the local leaderboard".italic().blue());
+        } else {
+            let submit = match config.auto_submit {
+                Some(auto_submit) => auto_submit,
+                None => {
+                    print!("{}", "Would you like to submit to the leaderboard? [y/N] ".blue());
+                    read_yes_no_prompt(true)?
                 }
+            };
+            if submit {
+                submit_result(client, uid, config, &gid, &result)?;
             }
         }
 
@@ -171,38 +412,40 @@ fn run_normal(client: &reqwest::blocking::Client, authenticated: bool, mut save_
     Ok(())
 }
 
-fn run_custom(client: &reqwest::blocking::Client, authenticated: bool, oid: String, mut save_data: Option<SaveData>) -> Result<()> {
+pub(crate) fn run_custom(client: &reqwest::blocking::Client, authenticated: bool, config: &Config, oid: String, mut save_data: Option<SaveData>) -> Result<()> {
     debug!("custom game oid {oid}");
-    let game_info = get_custom_game(&client, &oid)?;
-    println!(
-        "{} {}{}{} {} {} {}",
-        "Loaded custom game".blue(),
-        game_info.attribute_data.title.bold().blue(),
-        "! (".blue(),
-        game_info.denormalized_vote_count.to_string().bold().blue(),
-        "likes,".blue(),
-        game_info.execution_count.to_string().bold().blue(),
-        "plays)".blue()
-    );
+    let (game_info, attributes) = match load_custom_game(client, &oid, false) {
+        Ok(game_info) => {
+            let attributes = game_info.attribute_data.clone();
+            (Some(game_info), attributes)
+        }
+        // a saved game can still be resumed using the attributes stored with it
+        Err(e) => match save_data.as_ref().and_then(|d| d.custom_attributes.clone()) {
+            Some(attributes) => {
+                eprintln!("{} {}", "Could not load custom game, resuming from save:".red(), e.to_string().red());
+                (None, attributes)
+            }
+            None => return Err(e),
+        },
+    };
+    if let Some(game_info) = &game_info {
+        println!("{}", "Loaded custom game!".blue());
+        print_game_card(client, game_info, &oid, authenticated)?;
+    }
 
+    let judge = create_judge(client, config, true, &oid, &attributes.judging_criteria)?;
     loop {
-        let save_template = SaveData::template(true, oid.clone());
+        let mut save_template = SaveData::custom_template(oid.clone(), attributes.clone());
+        save_template.chain = save_data.as_ref().map(|d| d.chain.clone()).unwrap_or_default();
 
         do_game(
-            true,
-            save_data.as_ref().map(|d| d.prev_guess.as_ref()).unwrap_or(&game_info.attribute_data.start_word),
-            save_data.as_ref().map(|d| d.prev_emoji.as_ref()).unwrap_or(&game_info.attribute_data.start_emoji),
-            &game_info.attribute_data.judging_criteria,
-            &game_info.attribute_data.judging_criteria_loss,
-            save_template,
-            |guess, prev_guess| {
-                let request = CustomGameRequest {
-                    oid: oid.clone(),
-                    guess: guess.to_string(),
-                    prev: prev_guess.to_string(),
-                };
-                do_custom_guess(&client, request)
-            }
+            Some(&oid),
+            save_data.as_ref().map(|d| d.prev_guess.as_ref()).unwrap_or(&attributes.start_word),
+            save_data.as_ref().map(|d| d.prev_emoji.as_ref()).unwrap_or(&attributes.start_emoji),
+            &attributes.judging_criteria,
+            &attributes.judging_criteria_loss,
+            Some(save_template),
+            judge.as_ref(),
         )?;
 
         print!("{}", "Play again? [y/N] ".blue());
@@ -214,10 +457,12 @@ fn run_custom(client: &reqwest::blocking::Client, authenticated: bool, oid: Stri
         save_data = None;
     }
 
-    if authenticated && !game_info.has_liked() {
+    if let Some(game_info) = game_info.filter(|g| authenticated && g.vote_state() == VoteState::None) {
         print!("{}", "Like this custom game? [y/N] ".blue());
         if read_yes_no_prompt(true)? {
-            if !like_custom_game(&client, &game_info.id)? {
+            if vote_custom_game(client, &game_info.id, true)? {
+                forget_custom_game(&oid)?;
+            } else {
                 println!("{}", "like unsuccessful".red());
             }
         }
@@ -226,6 +471,29 @@ fn run_custom(client: &reqwest::blocking::Client, authenticated: bool, oid: Stri
     Ok(())
 }
 
+/// Plays normal games judged by replaying the results of guesses that have been judged before, without going online
+fn run_practice() -> Result<()> {
+    let judge = PracticeJudge::load()?;
+    if judge.is_empty() {
+        println!("{}", "You haven't won any guesses yet! Play some normal games first, then come back to practise.".blue());
+        return Ok(());
+    }
+    println!("{}", "Practice mode: guesses get the same result they got before, and new guesses lose".italic().blue());
+
+    loop {
+        if do_game(None, "rock", "🪨", "beats", "does not beat", None, &judge)?.exited {
+            break;
+        }
+
+        print!("{}", "Play again? [y/N] ".blue());
+        if !read_yes_no_prompt(true)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
 
@@ -240,6 +508,26 @@ fn main() -> Result<()> {
         .init();
 
     let args = Args::parse();
+    let mut config = Config::load()?;
+    config.apply_color();
+
+    match args.command {
+        Some(Command::Leaderboard { local }) => return show_leaderboard(local),
+        Some(Command::Config { command }) => return run_config_command(config, command),
+        Some(Command::Practice) => return run_practice(),
+        _ => {},
+    }
+
+    config.leaderboard = args.leaderboard.or(config.leaderboard);
+    config.browser = args.browser.or(config.browser);
+    config.browser_profile = args.browser_profile.clone().or(config.browser_profile);
+    config.judge = args.judge.or(config.judge);
+    config.rules_file = args.rules_file.clone().or(config.rules_file);
+    config.llm_url = args.llm_url.clone().or(config.llm_url);
+    config.llm_model = args.llm_model.clone().or(config.llm_model);
+    if let Some(api_base) = &config.api_base {
+        set_api_base(api_base.clone());
+    }
 
     let cookie_jar = Arc::new(reqwest::cookie::Jar::default());
     let client = reqwest::blocking::Client::builder()
@@ -247,29 +535,81 @@ fn main() -> Result<()> {
         .cookie_provider(Arc::clone(&cookie_jar))
         .build()?;
 
-    let accounts = get_session_cookies(&client, &cookie_jar)?;
-    let uid = if let Some(account) = auth_prompt(accounts)? {
-        add_auth_cookie(&cookie_jar, &account.auth_cookie);
-        Some(account.user_id)
-    } else {
+    match args.command {
+        Some(Command::Login) => return login(&client, &cookie_jar),
+        Some(Command::Logout) => return logout(&client),
+        Some(Command::Fav { command }) => return run_fav_command(&client, command),
+        Some(Command::Bot(bot_args)) => return run_bot(&client, &config, bot_args),
+        Some(Command::Party(party_args)) => return run_party(&client, &config, party_args),
+        Some(Command::Suggest { word, custom }) => {
+            let oid = custom.map(|custom| resolve_custom_oid(&client, &custom)).transpose()?;
+            return print_suggestions(oid.as_deref(), &word);
+        }
+        Some(Command::Lookup { prev, guess, custom }) => {
+            let oid = custom.map(|custom| resolve_custom_oid(&client, &custom)).transpose()?;
+            let attributes = oid.as_deref().map(|oid| load_custom_game(&client, oid, false)).transpose()?.map(|g| g.attribute_data);
+            let (judging_criteria_win, judging_criteria_loss) = match &attributes {
+                Some(attributes) => (attributes.judging_criteria.as_str(), attributes.judging_criteria_loss.as_str()),
+                None => ("beats", "does not beat"),
+            };
+            return show_lookup(oid.as_deref(), judging_criteria_win, judging_criteria_loss, &prev, &guess);
+        }
+        Some(Command::Accounts) => return list_accounts(&client, &cookie_jar, config.browser, config.browser_profile.as_deref()),
+        _ => {},
+    }
+
+    let auth_token = match &args.auth_token_file {
+        Some(path) => Some(std::fs::read_to_string(path)?),
+        None => args.auth_token.clone(),
+    };
+
+    // local judges don't need an account, so games can be played offline
+    let offline = args.command.is_none() && config.judge.unwrap_or_default() != JudgeBackend::Remote;
+
+    let account = if offline {
         None
+    } else if let Some(token) = auth_token {
+        let account = get_token_account(&client, &cookie_jar, &token)?;
+        println!("Using account: @{}", account.username);
+        Some(account)
+    } else if let Some(account) = get_stored_session(&client, &cookie_jar)? {
+        println!("Using account: @{}", account.username);
+        Some(account)
+    } else {
+        let accounts = get_session_cookies(&client, &cookie_jar, config.browser, config.browser_profile.as_deref())?;
+        auth_prompt(accounts, config.account.as_deref())?
     };
+    if let Some(account) = &account {
+        set_active_session(Arc::clone(&cookie_jar), account);
+    }
+    let uid = account.as_ref().map(|a| a.user_id.clone());
+
+    match args.command {
+        Some(Command::SubmitPending) => return submit_pending(&client, uid.as_deref(), true),
+        Some(Command::Profile { handle }) => return show_profile(&client, handle.as_deref(), account.as_ref()),
+        Some(Command::Custom { command }) => return run_custom_command(&client, account.as_ref(), &config, command),
+        _ => {},
+    }
+    if !offline {
+        submit_pending(&client, uid.as_deref(), false)?;
+    }
 
     if let Some(save) = SaveData::load()? {
         println!("{}", "Loaded saved game".italic().blue());
         if save.is_custom {
-            run_custom(&client, uid.is_some(), save.gid_oid.clone(), Some(save))?;
+            run_custom(&client, uid.is_some(), &config, save.gid_oid.clone(), Some(save))?;
         } else {
-            run_normal(&client, uid.is_some(), Some(save))?;
+            run_normal(&client, uid.as_deref(), &config, Some(save))?;
         }
     } else {
-        if let Some(custom_username) = args.custom_username {
-            let username = custom_username.strip_prefix('@').unwrap_or(&custom_username);
-            let oid = get_user_id(&client, username)?;
+        if let Some(custom) = &args.custom {
+            let oid = resolve_custom_oid(&client, custom)?;
 
-            run_custom(&client, uid.is_some(), oid, None)?;
+            run_custom(&client, uid.is_some(), &config, oid, None)?;
+        } else if let Some(oid) = pick_favourite()? {
+            run_custom(&client, uid.is_some(), &config, oid, None)?;
         } else {
-            run_normal(&client, uid.is_some(), None)?;
+            run_normal(&client, uid.as_deref(), &config, None)?;
         }
     }
 
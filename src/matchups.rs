@@ -0,0 +1,136 @@
+use std::cmp::Reverse;
+use colored::Colorize;
+use color_eyre::Result;
+use crate::api::GameResponseInner;
+use crate::cache::now;
+use crate::save::{load_json, save_json};
+
+const MATCHUPS_FILE: &str = "wbr_matchups.json";
+
+/// A guess judged by What Beats Rock
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub(crate) struct Matchup {
+    /// The custom game the guess was made in, or `None` for normal games
+    #[serde(default)]
+    pub(crate) oid: Option<String>,
+    pub(crate) prev: String,
+    pub(crate) guess: String,
+    pub(crate) wins: bool,
+    pub(crate) emoji: String,
+    pub(crate) reason: String,
+    pub(crate) cache_count: Option<u64>,
+    pub(crate) judged_at: u64,
+    /// How many times the guess has won
+    #[serde(default)]
+    pub(crate) times_won: u64,
+}
+
+impl Matchup {
+    pub(crate) fn is(&self, oid: Option<&str>, prev: &str, guess: &str) -> bool {
+        self.oid.as_deref() == oid && self.prev.eq_ignore_ascii_case(prev) && self.guess.eq_ignore_ascii_case(guess)
+    }
+
+    pub(crate) fn response(&self) -> GameResponseInner {
+        GameResponseInner {
+            guess_wins: self.wins,
+            guess_emoji: self.emoji.clone(),
+            reason: self.reason.clone(),
+            cache_count: self.cache_count,
+        }
+    }
+}
+
+/// Guesses judged by What Beats Rock, used to judge practice games offline and to look up matchups
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct Matchups {
+    pub(crate) matchups: Vec<Matchup>,
+}
+
+impl Matchups {
+    pub(crate) fn load() -> Result<Self> {
+        load_json(MATCHUPS_FILE)
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        save_json(MATCHUPS_FILE, self)
+    }
+
+    /// Adds a judged guess to the matchups file, replacing any previous result for it
+    pub(crate) fn record(oid: Option<&str>, prev: &str, guess: &str, response: &GameResponseInner) -> Result<()> {
+        let mut matchups = Self::load()?;
+        let times_won = matchups.find(oid, prev, guess).map(|m| m.times_won).unwrap_or(0) + u64::from(response.guess_wins);
+        matchups.matchups.retain(|m| !m.is(oid, prev, guess));
+        matchups.matchups.push(Matchup {
+            oid: oid.map(str::to_string),
+            prev: prev.to_string(),
+            guess: guess.to_string(),
+            wins: response.guess_wins,
+            emoji: response.guess_emoji.clone(),
+            reason: response.reason.clone(),
+            cache_count: response.cache_count,
+            judged_at: now(),
+            times_won,
+        });
+        matchups.save()
+    }
+
+    pub(crate) fn find(&self, oid: Option<&str>, prev: &str, guess: &str) -> Option<&Matchup> {
+        self.matchups.iter().find(|m| m.is(oid, prev, guess))
+    }
+
+    /// Returns the guesses that have been judged against a word, winning guesses first,
+    /// ranked by how often they won and how many others have guessed them
+    pub(crate) fn suggestions(&self, oid: Option<&str>, prev: &str) -> Vec<&Matchup> {
+        let mut suggestions = self.matchups
+            .iter()
+            .filter(|m| m.oid.as_deref() == oid && m.prev.eq_ignore_ascii_case(prev))
+            .collect::<Vec<_>>();
+        suggestions.sort_by_key(|m| Reverse((m.wins, m.times_won, m.cache_count.unwrap_or(0))));
+        suggestions
+    }
+}
+
+/// Shows whether a guess has been judged before, and what the result was, using the game's judging criteria
+pub(crate) fn show_lookup(oid: Option<&str>, judging_criteria_win: &str, judging_criteria_loss: &str, prev: &str, guess: &str) -> Result<()> {
+    let matchups = Matchups::load()?;
+    let Some(matchup) = matchups.find(oid, prev, guess) else {
+        println!("{}", format!("{guess} vs {prev} hasn't been judged before").italic().blue());
+        return Ok(());
+    };
+
+    if matchup.wins {
+        println!("{} {} {} {}", matchup.guess.bold().green(), matchup.emoji.bold().green(), judging_criteria_win.green(), matchup.prev.bold().green());
+        println!("{}", matchup.reason.green());
+    } else {
+        println!("{} {} {} {}", matchup.guess.bold().red(), matchup.emoji.bold().red(), judging_criteria_loss.red(), matchup.prev.bold().red());
+        println!("{}", matchup.reason.red());
+    }
+    if let Some(n) = matchup.cache_count {
+        println!("{} {}", n.to_string().bold().blue(), "others had guessed this".blue());
+    }
+    Ok(())
+}
+
+/// Lists guesses that have beaten a word before, and guesses that lost against it
+pub(crate) fn print_suggestions(oid: Option<&str>, prev: &str) -> Result<()> {
+    let matchups = Matchups::load()?;
+    let suggestions = matchups.suggestions(oid, prev);
+    if suggestions.is_empty() {
+        println!("{}", format!("No guesses have been judged against {prev} yet").italic().blue());
+        return Ok(());
+    }
+
+    for matchup in suggestions {
+        if matchup.wins {
+            print!("  {} {}", matchup.guess.bold().green(), matchup.emoji.green());
+            print!(" {}", format!("(won {}x", matchup.times_won).dimmed());
+            if let Some(n) = matchup.cache_count {
+                print!("{}", format!(", guessed by {n} others").dimmed());
+            }
+            println!("{}", ")".dimmed());
+        } else {
+            println!("  {} {} {}", matchup.guess.bold().red(), matchup.emoji.red(), "(lost before)".dimmed());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use colored::Colorize;
+use color_eyre::Result;
+use crate::api::{submit_score, submit_score_authenticated, AuthenticatedLeaderboardRequest, ErrorResponse, LeaderboardRequest};
+use crate::save::{data_file, load_json, save_json};
+
+const OUTBOX_FILE: &str = "wbr_outbox.json";
+
+/// A leaderboard submission that could not be sent and is waiting to be retried
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum PendingSubmission {
+    Anonymous(LeaderboardRequest),
+    Authenticated {
+        /// The account that made the submission, which is the only one it may be sent with
+        user_id: String,
+        #[serde(flatten)]
+        request: AuthenticatedLeaderboardRequest,
+    },
+}
+
+impl PendingSubmission {
+    pub(crate) fn submit(&self, client: &reqwest::blocking::Client) -> Result<bool> {
+        match self {
+            Self::Anonymous(request) => submit_score(client, request.clone()),
+            Self::Authenticated { request, .. } => submit_score_authenticated(client, request.clone()),
+        }
+    }
+
+    pub(crate) fn score(&self) -> u64 {
+        match self {
+            Self::Anonymous(request) => request.score,
+            Self::Authenticated { request, .. } => request.score,
+        }
+    }
+
+    /// Whether the submission can be sent with the current account
+    fn can_submit(&self, uid: Option<&str>) -> bool {
+        match self {
+            Self::Anonymous(_) => true,
+            Self::Authenticated { user_id, .. } => uid == Some(user_id.as_str()),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub(crate) struct Outbox {
+    pub(crate) pending: Vec<PendingSubmission>,
+}
+
+impl Outbox {
+    pub(crate) fn load() -> Result<Self> {
+        load_json(OUTBOX_FILE)
+    }
+
+    pub(crate) fn save(&self) -> Result<()> {
+        let path = data_file(OUTBOX_FILE)?;
+        if self.pending.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+        } else {
+            save_json(OUTBOX_FILE, self)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a submission to the persistent outbox
+    pub(crate) fn queue(submission: PendingSubmission) -> Result<()> {
+        let mut outbox = Self::load()?;
+        outbox.pending.push(submission);
+        outbox.save()
+    }
+}
+
+pub(crate) enum SubmitOutcome {
+    Submitted,
+    Queued,
+    /// The server refused the submission, so it was not queued
+    Rejected {
+        reason: String,
+        /// Whether it was refused because of the initials, so it's worth trying again with different ones
+        bad_initials: bool,
+    },
+}
+
+/// Submits a score, queueing it in the outbox if it couldn't be sent
+pub(crate) fn submit_or_queue(client: &reqwest::blocking::Client, submission: PendingSubmission) -> Result<SubmitOutcome> {
+    match submission.submit(client) {
+        Ok(true) => Ok(SubmitOutcome::Submitted),
+        Ok(false) => Ok(SubmitOutcome::Rejected { reason: "submit score unsuccessful".to_string(), bad_initials: false }),
+        Err(e) => {
+            if let Some(error) = e.downcast_ref::<ErrorResponse>() {
+                return Ok(SubmitOutcome::Rejected { reason: error.to_string(), bad_initials: error.is_about_initials() });
+            }
+            eprintln!("{} {}", "Could not submit score:".red(), e.to_string().red());
+            Outbox::queue(submission)?;
+            println!("{}", "Your score has been saved and will be submitted next time you start wbr, or when you run `wbr submit-pending`".blue());
+            Ok(SubmitOutcome::Queued)
+        }
+    }
+}
+
+/// Retries all submissions in the outbox, keeping those that still fail.
+///
+/// Authenticated submissions are only retried when logged in to the account that made them. If `verbose` is false,
+/// nothing is printed when the outbox is empty.
+pub(crate) fn submit_pending(client: &reqwest::blocking::Client, uid: Option<&str>, verbose: bool) -> Result<()> {
+    let outbox = Outbox::load()?;
+    if outbox.pending.is_empty() {
+        if verbose {
+            println!("{}", "No pending leaderboard submissions".blue());
+        }
+        return Ok(());
+    }
+
+    let mut submitted = 0;
+    let mut rejected = 0;
+    let mut remaining = Vec::new();
+    for submission in outbox.pending {
+        if !submission.can_submit(uid) {
+            remaining.push(submission);
+            continue;
+        }
+
+        match submission.submit(client) {
+            Ok(true) => submitted += 1,
+            Ok(false) => {
+                println!("{} {}", "Leaderboard rejected pending score".red(), submission.score().to_string().bold().red());
+                rejected += 1;
+            }
+            Err(e) if e.downcast_ref::<ErrorResponse>().is_some() => {
+                println!("{} {}{} {}", "Leaderboard rejected pending score".red(), submission.score().to_string().bold().red(), ":".red(), e.to_string().red());
+                rejected += 1;
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Could not submit pending score:".red(), e.to_string().red());
+                remaining.push(submission);
+            }
+        }
+    }
+
+    if submitted > 0 {
+        println!("{} {} {}", "Submitted".green(), submitted.to_string().bold().green(), "pending leaderboard scores".green());
+    }
+    if rejected > 0 {
+        println!("{} {} {}", "Discarded".red(), rejected.to_string().bold().red(), "rejected leaderboard scores".red());
+    }
+    if !remaining.is_empty() {
+        let needs_login = remaining.iter().any(|s| !s.can_submit(uid));
+        println!("{} {} {}", remaining.len().to_string().bold().blue(), "leaderboard scores still pending".blue(), if needs_login { "(some need you to be logged in to the account that made them)" } else { "" }.blue());
+    }
+
+    Outbox { pending: remaining }.save()
+}
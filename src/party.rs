@@ -0,0 +1,218 @@
+use std::cmp::Reverse;
+use std::io::Write;
+use clap::Args;
+use colored::Colorize;
+use color_eyre::eyre::{bail, Result};
+use crate::config::Config;
+use crate::custom::{load_custom_game, resolve_custom_oid};
+use crate::guess::{in_chain, normalise_guess};
+use crate::judge::{create_judge, Judge};
+use crate::matchups::print_suggestions;
+use crate::{do_game, print_loss, print_prompt, print_win};
+
+#[derive(Args, Debug)]
+pub(crate) struct PartyArgs {
+    /// Names of the players, separated by commas, in the order they take turns
+    #[arg(long, value_delimiter = ',', required = true)]
+    players: Vec<String>,
+
+    /// Give each player their own chain from the start word, instead of taking turns in one chain
+    #[arg(long)]
+    separate: bool,
+
+    /// Play a custom game, given the creator's handle, their user ID, or a link to the game
+    #[arg(short, long)]
+    custom: Option<String>,
+}
+
+struct Player {
+    name: String,
+    score: u64,
+    /// The turn the player lost on, or `None` if they are still in
+    eliminated_at: Option<u64>,
+}
+
+/// The start word and judging criteria of the game being played
+struct PartyGame {
+    oid: Option<String>,
+    start_guess: String,
+    start_emoji: String,
+    judging_criteria_win: String,
+    judging_criteria_loss: String,
+}
+
+impl PartyGame {
+    fn create_judge<'a>(&self, client: &'a reqwest::blocking::Client, config: &Config) -> Result<Box<dyn Judge + 'a>> {
+        let gid_oid = self.oid.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        create_judge(client, config, self.oid.is_some(), &gid_oid, &self.judging_criteria_win)
+    }
+}
+
+/// Reads a player's guess, returning `None` if they want to end the party
+fn read_guess(name: &str, game: &PartyGame, prev_guess: &str, prev_emoji: &str, chain: &[String]) -> Result<Option<String>> {
+    loop {
+        print!("{} ", format!("{name}:").bold());
+        print_prompt(&game.judging_criteria_win, prev_guess, prev_emoji);
+        std::io::stdout().flush()?;
+        let mut guess = String::new();
+        std::io::stdin().read_line(&mut guess)?;
+        let guess = guess.trim();
+
+        if guess == "EXIT" {
+            return Ok(None);
+        }
+
+        if guess == "/hint" {
+            print_suggestions(game.oid.as_deref(), prev_guess)?;
+            continue;
+        }
+
+        match normalise_guess(guess) {
+            Ok(guess) if in_chain(chain, &guess) => {
+                println!("{}", format!("{guess} has already been used in this game! Each word can only be used once, so try something else.").red());
+            }
+            Ok(guess) => return Ok(Some(guess)),
+            Err(e) => println!("{}", e.red()),
+        }
+    }
+}
+
+/// Players take turns in one chain. A player who loses is out, and the next player has to beat the same word.
+fn play_shared(client: &reqwest::blocking::Client, config: &Config, game: &PartyGame, players: &mut [Player]) -> Result<()> {
+    let judge = game.create_judge(client, config)?;
+    let mut chain = vec![game.start_guess.clone()];
+    let mut prev_guess = game.start_guess.clone();
+    let mut prev_emoji = game.start_emoji.clone();
+    let mut turn: u64 = 0;
+
+    for current in (0..players.len()).cycle() {
+        if players.iter().filter(|p| p.eliminated_at.is_none()).count() <= 1 {
+            break;
+        }
+        let player = &mut players[current];
+        if player.eliminated_at.is_some() {
+            continue;
+        }
+        turn += 1;
+
+        let (guess, response) = loop {
+            let Some(guess) = read_guess(&player.name, game, &prev_guess, &prev_emoji, &chain)? else {
+                println!("{}", "Party ended early".italic().blue());
+                return Ok(());
+            };
+            match judge.judge(&guess, &prev_guess) {
+                Ok(response) => break (guess, response),
+                Err(e) => eprintln!("{} {}", "API error:".red(), e.to_string().red()),
+            }
+        };
+
+        if response.guess_wins {
+            print_win(&guess, &response.guess_emoji, &game.judging_criteria_win, &prev_guess, &prev_emoji);
+            println!("{}", response.reason.green());
+            player.score += 1;
+            chain.push(guess.clone());
+            prev_guess = guess;
+            prev_emoji = response.guess_emoji;
+        } else {
+            print_loss(&guess, &response.guess_emoji, &game.judging_criteria_loss, &prev_guess, &prev_emoji);
+            println!("{}", response.reason.red());
+            println!("{}", format!("{} is out!", player.name).bold().red());
+            player.eliminated_at = Some(turn);
+        }
+    }
+
+    Ok(())
+}
+
+/// Each player plays their own game from the same start word, one after another
+fn play_separate(client: &reqwest::blocking::Client, config: &Config, game: &PartyGame, players: &mut [Player]) -> Result<()> {
+    for (turn, player) in players.iter_mut().enumerate() {
+        println!("{}", format!("{}'s turn!", player.name).bold().blue());
+        let judge = game.create_judge(client, config)?;
+        let result = do_game(
+            game.oid.as_deref(),
+            &game.start_guess,
+            &game.start_emoji,
+            &game.judging_criteria_win,
+            &game.judging_criteria_loss,
+            None,
+            judge.as_ref(),
+        )?;
+        player.score = result.score;
+        player.eliminated_at = Some(turn as u64 + 1);
+        if result.exited {
+            println!("{}", "Party ended early".italic().blue());
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn print_ranking(players: &mut [Player], separate: bool) {
+    if separate {
+        players.sort_by_key(|p| Reverse(p.score));
+    } else {
+        // whoever is still in wins, then whoever lasted longest
+        players.sort_by_key(|p| Reverse((p.eliminated_at.is_none(), p.eliminated_at, p.score)));
+    }
+
+    println!("{}", "Final ranking:".bold().blue());
+    for (n, player) in players.iter().enumerate() {
+        println!(
+            "{} {} {} {}",
+            format!("{}.", n + 1).bold().blue(),
+            player.name.bold(),
+            player.score.to_string().bold().green(),
+            "correct guesses".blue()
+        );
+    }
+}
+
+/// Plays a game with several players taking turns on one computer
+pub(crate) fn run_party(client: &reqwest::blocking::Client, config: &Config, args: PartyArgs) -> Result<()> {
+    let mut players = Vec::<Player>::new();
+    for name in args.players.iter().map(|name| name.trim()) {
+        if name.is_empty() {
+            bail!("Player names can't be empty");
+        }
+        if players.iter().any(|p| p.name.eq_ignore_ascii_case(name)) {
+            bail!("{name} is playing twice!");
+        }
+        players.push(Player { name: name.to_string(), score: 0, eliminated_at: None });
+    }
+    if players.len() < 2 {
+        bail!("A party needs at least 2 players");
+    }
+
+    let game = match args.custom {
+        Some(custom) => {
+            let oid = resolve_custom_oid(client, &custom)?;
+            let attributes = load_custom_game(client, &oid, false)?.attribute_data;
+            println!("{} {}", "Loaded custom game:".blue(), attributes.title.bold().blue());
+            PartyGame {
+                oid: Some(oid),
+                start_guess: attributes.start_word,
+                start_emoji: attributes.start_emoji,
+                judging_criteria_win: attributes.judging_criteria,
+                judging_criteria_loss: attributes.judging_criteria_loss,
+            }
+        }
+        None => PartyGame {
+            oid: None,
+            start_guess: "rock".to_string(),
+            start_emoji: "🪨".to_string(),
+            judging_criteria_win: "beats".to_string(),
+            judging_criteria_loss: "does not beat".to_string(),
+        },
+    };
+
+    if args.separate {
+        play_separate(client, config, &game, &mut players)?;
+        // if the party ended early, only rank the players who got a turn
+        players.retain(|p| p.eliminated_at.is_some());
+    } else {
+        play_shared(client, config, &game, &mut players)?;
+    }
+    print_ranking(&mut players, args.separate);
+    Ok(())
+}
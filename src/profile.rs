@@ -0,0 +1,55 @@
+use colored::Colorize;
+use color_eyre::eyre::{eyre, Result};
+use crate::api::{get_profile, get_profile_by_handle, get_user_scores};
+use crate::auth::AuthInfo;
+use crate::custom::fetch_custom_game;
+
+const MAX_SCORES: usize = 5;
+
+/// Formats an ISO 8601 timestamp as just the date
+fn format_date(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+/// Shows the public profile of a user, or the current account if no handle is given
+pub(crate) fn show_profile(client: &reqwest::blocking::Client, handle: Option<&str>, account: Option<&AuthInfo>) -> Result<()> {
+    let profile = match (handle, account) {
+        (Some(handle), _) => get_profile_by_handle(client, handle.strip_prefix('@').unwrap_or(handle))?,
+        (None, Some(account)) => get_profile(client, &account.user_id)?,
+        (None, None) => return Err(eyre!("Not logged in! Specify the handle of the user whose profile you want to see.")),
+    };
+
+    println!("{}", format!("@{}", profile.handle).bold().blue());
+    if let Some(created_at) = &profile.created_at {
+        println!("{} {}", "Joined".blue(), format_date(created_at).bold().blue());
+    }
+
+    match fetch_custom_game(client, &profile.id, false) {
+        Ok(Some(game)) => println!(
+            "{} {} {}{} {} {} {}",
+            "Custom game:".blue(),
+            game.attribute_data.title.bold().blue(),
+            "(".blue(),
+            game.denormalized_vote_count.to_string().bold().blue(),
+            "likes,".blue(),
+            game.execution_count.to_string().bold().blue(),
+            "plays)".blue()
+        ),
+        Ok(None) => println!("{}", "No custom game".italic().blue()),
+        Err(e) => eprintln!("{} {}", "Could not load custom game:".red(), e.to_string().red()),
+    }
+
+    match get_user_scores(client, &profile.id) {
+        Ok(scores) if scores.is_empty() => println!("{}", "No leaderboard scores".italic().blue()),
+        Ok(scores) => {
+            println!("{}", "Best leaderboard scores:".blue());
+            for score in scores.iter().take(MAX_SCORES) {
+                let date = score.created_at.as_deref().map(format_date).unwrap_or_default();
+                println!("  {} {} {}", score.score.to_string().bold().green(), score.text.italic(), date.dimmed());
+            }
+        }
+        Err(e) => eprintln!("{} {}", "Could not load leaderboard scores:".red(), e.to_string().red()),
+    }
+
+    Ok(())
+}
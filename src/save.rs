@@ -1,14 +1,68 @@
-use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use color_eyre::Result;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::api::CustomGameAttributes;
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+const SAVE_FILE: &str = "wbr_save.json";
+
+/// Returns the path of a file in the data local directory
+pub(crate) fn data_file(name: &str) -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir().ok_or(std::io::Error::new(ErrorKind::NotFound, "Could not find data local directory!"))?;
+    Ok(data_dir.join(name))
+}
+
+/// Loads a JSON file from the data local directory, or the default if it doesn't exist.
+/// A corrupt file is moved aside to `<name>.corrupt` rather than failing, so it doesn't stop wbr from working.
+pub(crate) fn load_json<T: DeserializeOwned + Default>(name: &str) -> Result<T> {
+    let path = data_file(name)?;
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let json = std::fs::read_to_string(&path)?;
+    match serde_json::from_str::<T>(&json) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            let backup = data_file(&format!("{name}.corrupt"))?;
+            warn!("{} is corrupt ({e}), moving it to {}", path.display(), backup.display());
+            std::fs::rename(&path, &backup)?;
+            Ok(T::default())
+        }
+    }
+}
+
+/// Saves a value as a JSON file in the data local directory
+pub(crate) fn save_json<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    std::fs::write(data_file(name)?, serde_json::to_string(value)?)?;
+    Ok(())
+}
+
+/// Writes a file that only the current user can read
+pub(crate) fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 pub(crate) struct SaveData {
     pub(crate) is_custom: bool,
     pub(crate) gid_oid: String,
     pub(crate) prev_guess: String,
     pub(crate) prev_emoji: String,
     pub(crate) score: u64,
+    /// The custom game being played, so it can be resumed if the game can't be fetched
+    #[serde(default)]
+    pub(crate) custom_attributes: Option<CustomGameAttributes>,
+    /// Every word used so far, so repeated guesses can be caught after resuming
+    #[serde(default)]
+    pub(crate) chain: Vec<String>,
 }
 
 impl SaveData {
@@ -20,25 +74,28 @@ impl SaveData {
         }
     }
 
-    pub(crate) fn update(&mut self, prev_guess: String, prev_emoji: String, score: u64) {
+    pub(crate) fn custom_template(oid: String, attributes: CustomGameAttributes) -> Self {
+        Self {
+            custom_attributes: Some(attributes),
+            ..Self::template(true, oid)
+        }
+    }
+
+    pub(crate) fn update(&mut self, prev_guess: String, prev_emoji: String, score: u64, chain: Vec<String>) {
+        self.chain = chain;
         self.prev_guess = prev_guess;
         self.prev_emoji = prev_emoji;
         self.score = score;
     }
 
-    fn save_file() -> Result<PathBuf> {
-        let save_dir = dirs::data_local_dir().ok_or(std::io::Error::new(ErrorKind::NotFound, "Could not find data local directory!"))?;
-        Ok(save_dir.join("wbr_save.json"))
-    }
-
     pub(crate) fn save(&self) -> Result<()> {
         let json = serde_json::to_string(self)?;
-        std::fs::write(Self::save_file()?, &json)?;
+        std::fs::write(data_file(SAVE_FILE)?, &json)?;
         Ok(())
     }
 
     pub(crate) fn load() -> Result<Option<Self>> {
-        let path = Self::save_file()?;
+        let path = data_file(SAVE_FILE)?;
         if path.exists() {
             let json = std::fs::read_to_string(&path)?;
             std::fs::remove_file(&path)?;
@@ -48,15 +105,3 @@ impl SaveData {
         }
     }
 }
-
-impl Default for SaveData {
-    fn default() -> Self {
-        Self {
-            is_custom: false,
-            gid_oid: String::new(),
-            prev_guess: String::new(),
-            prev_emoji: String::new(),
-            score: 0,
-        }
-    }
-}
\ No newline at end of file